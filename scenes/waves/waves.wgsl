@@ -14,6 +14,14 @@ var<uniform> camera: CameraMatrix;
 @group(0) @binding(1)
 var<uniform> wave_render_params: WaveRenderParams;
 
+// builtin, set when the gpu converts our linear output to srgb
+struct Output {
+    srgb: u32,
+};
+
+@group(0) @binding(2)
+var<uniform> output: Output;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
 };
@@ -59,7 +67,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let diff = max(dot(N, lightDir), 0.0);
     let diffuse = diff * lightColor;
 
-    let result = (ambient + diffuse) * wave_render_params.colour;
+    var result = (ambient + diffuse) * wave_render_params.colour;
+    if (output.srgb == 0u) {
+        result = pow(result, vec3<f32>(1.0 / 2.2));
+    }
 
     return vec4<f32>(result, 1.0);
 }
\ No newline at end of file
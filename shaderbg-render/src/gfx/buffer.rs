@@ -92,3 +92,21 @@ impl Default for CameraMatrix {
         CameraMatrix::new()
     }
 }
+
+// tells scenes how their output is encoded
+// when srgb is set, shaders output linear colours and the gpu encodes them
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Output {
+    srgb: u32,
+    _spacer: [u32; 3],
+}
+
+impl Output {
+    pub fn new(format: wgpu::TextureFormat) -> Self {
+        Output {
+            srgb: format.is_srgb() as u32,
+            _spacer: [0; 3],
+        }
+    }
+}
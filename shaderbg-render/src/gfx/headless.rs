@@ -0,0 +1,241 @@
+/*
+ * Renders scenes without a window, into an owned texture that can be read back
+ * used for thumbnails, exports and image tests
+ */
+use wgpu::{Device, Queue, Texture, TextureFormat, TextureView};
+
+use crate::{
+    gfx::{
+        buffer::{ShaderToy, Time},
+        AdapterOptions,
+    },
+    scene::Resources,
+};
+
+// srgb like the window surface, so frames read back are ready to save
+const HEADLESS_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    TooLarge { width: u32, height: u32, max: u32 },
+    Readback(wgpu::BufferAsyncError),
+}
+
+pub struct HeadlessGfx {
+    pub device: Device,
+    pub queue: Queue,
+    pub adapter_info: wgpu::AdapterInfo,
+    width: u32,
+    height: u32,
+    texture: Texture,
+    view: TextureView,
+}
+
+impl HeadlessGfx {
+    // wgpu's fallback adapter is also tried when no other adapter exists
+    pub async fn new(
+        width: u32,
+        height: u32,
+        options: &AdapterOptions,
+    ) -> Result<HeadlessGfx, HeadlessError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: options.backends,
+            ..Default::default()
+        });
+
+        let mut adapter = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(name) = options.name.as_ref() {
+            let search = name.to_lowercase();
+            adapter = instance
+                .enumerate_adapters(options.backends)
+                .find(|adapter| adapter.get_info().name.to_lowercase().contains(&search));
+            if adapter.is_none() {
+                eprintln!("no adapter matching \"{name}\", using the default");
+            }
+        }
+        if adapter.is_none() && !options.force_fallback {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: None,
+                    force_fallback_adapter: false,
+                })
+                .await;
+        }
+        if adapter.is_none() {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                })
+                .await;
+        }
+        let adapter = adapter.ok_or(HeadlessError::NoAdapter)?;
+
+        // fallback adapters often can't reach the default limits, so ask for what they have
+        let limits = adapter.limits();
+        let max = limits.max_texture_dimension_2d;
+        if width > max || height > max {
+            return Err(HeadlessError::TooLarge { width, height, max });
+        }
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    limits,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .map_err(HeadlessError::RequestDevice)?;
+
+        let (texture, view) = HeadlessGfx::create_target(&device, width, height);
+
+        Ok(HeadlessGfx {
+            device,
+            queue,
+            adapter_info: adapter.get_info(),
+            width,
+            height,
+            texture,
+            view,
+        })
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        HEADLESS_FORMAT
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn resized(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        (self.texture, self.view) = HeadlessGfx::create_target(&self.device, width, height);
+    }
+
+    fn create_target(device: &Device, width: u32, height: u32) -> (Texture, TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    pub fn render(&self, scene: &mut Resources, time: Time, shadertoy: ShaderToy) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless Encoder"),
+            });
+
+        scene.render(&self.queue, &self.view, &mut encoder, time, shadertoy);
+
+        self.queue.submit(Some(encoder.finish()));
+        self.device.poll(wgpu::MaintainBase::Wait);
+    }
+
+    // reads the last rendered frame, as tightly packed rgba8 rows from the top
+    pub fn read_rgba(&self) -> Result<Vec<u8>, HeadlessError> {
+        read_texture(
+            &self.device,
+            &self.queue,
+            &self.texture,
+            self.width.max(1),
+            self.height.max(1),
+        )
+    }
+}
+
+// copies a rgba8 texture back to the cpu
+// blocks until the copy is done, so this can't be used on the web
+pub(crate) fn read_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, HeadlessError> {
+    let row_bytes = width * 4;
+    // buffer copies need each row aligned
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row_bytes = row_bytes.div_ceil(alignment) * alignment;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Headless Readback"),
+        size: (padded_row_bytes * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Headless Readback Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::MaintainBase::Wait);
+
+    match rx.recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => return Err(HeadlessError::Readback(error)),
+        Err(_) => return Err(HeadlessError::Readback(wgpu::BufferAsyncError)),
+    }
+
+    let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+    }
+    buffer.unmap();
+
+    Ok(pixels)
+}
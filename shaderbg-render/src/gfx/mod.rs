@@ -1,30 +1,81 @@
-use wgpu::{Device, Instance, Queue, Surface, SurfaceConfiguration};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use wgpu::{Adapter, Device, Instance, Queue, Surface, SurfaceConfiguration};
+pub use wgpu::{Backends, PowerPreference, PresentMode};
 
 use crate::scene::Resources;
 
-use self::buffer::{ShaderToy, Time};
+use self::{
+    buffer::{ShaderToy, Time},
+    transition::{Transition, TransitionKind},
+};
 
 pub mod buffer;
 pub mod camera;
+pub mod headless;
+pub mod post_process;
+pub mod profiler;
+pub mod resolution;
+pub mod transition;
 pub mod ui;
 pub mod vertices;
+pub use headless::HeadlessGfx;
 pub use ui::Ui;
 
+// which gpu to draw with
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdapterOptions {
+    pub backends: Backends,
+    pub power_preference: PowerPreference,
+    // the first adapter with a name containing this, ignoring case
+    pub name: Option<String>,
+    pub force_fallback: bool,
+}
+
+impl Default for AdapterOptions {
+    fn default() -> AdapterOptions {
+        AdapterOptions {
+            backends: Backends::all(),
+            power_preference: PowerPreference::default(),
+            name: None,
+            force_fallback: false,
+        }
+    }
+}
+
+// adapters aren't enumerable on the web
+#[cfg(not(target_arch = "wasm32"))]
+pub fn list_adapters(backends: Backends) -> Vec<wgpu::AdapterInfo> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+
+    instance
+        .enumerate_adapters(backends)
+        .map(|adapter| adapter.get_info())
+        .collect()
+}
+
 // because we cannot create a surface on second thread,
 // we create a context on the main thread which is used
 // to construct Gfx on another thread
 pub struct GfxContext {
     pub instance: Instance,
     pub surface: Surface,
+    pub adapter_options: AdapterOptions,
 }
 
 impl GfxContext {
-    pub fn new<W>(window: &W) -> GfxContext
+    pub fn new<W>(window: &W, adapter_options: AdapterOptions) -> GfxContext
     where
         W: raw_window_handle::HasRawWindowHandle + raw_window_handle::HasRawDisplayHandle,
     {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
+            backends: adapter_options.backends,
             ..Default::default()
         });
 
@@ -34,52 +85,87 @@ impl GfxContext {
         // https://docs.rs/wgpu/latest/wgpu/struct.Instance.html#method.create_surface
         let surface = unsafe { instance.create_surface(window) }.unwrap();
 
-        GfxContext { instance, surface }
+        GfxContext {
+            instance,
+            surface,
+            adapter_options,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GfxError {
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    Validation(String),
+    OutOfMemory,
+    DeviceLost,
+}
+
+type ErrorCallback = Box<dyn Fn(&GfxError) + Send>;
+
+// shared with the device's uncaptured error handler, which may run on any wgpu call
+#[derive(Default)]
+struct ErrorState {
+    callback: Option<ErrorCallback>,
+    device_lost: bool,
+}
+
+impl ErrorState {
+    fn report(&mut self, error: GfxError) {
+        if matches!(error, GfxError::DeviceLost | GfxError::OutOfMemory) {
+            self.device_lost = true;
+        }
+
+        match self.callback.as_ref() {
+            Some(callback) => callback(&error),
+            None => eprintln!("graphics error: {error:?}"),
+        }
     }
 }
 
 pub struct Gfx {
     pub config: SurfaceConfiguration,
+    instance: Instance,
+    adapter: Adapter,
+    adapter_options: AdapterOptions,
     surface: Surface,
+    present_modes: Vec<PresentMode>,
     pub device: Device,
     pub queue: Queue,
     pub ui: Option<Ui>,
+    transition: Option<Transition>,
+    errors: Arc<Mutex<ErrorState>>,
 }
 
 impl Gfx {
     pub async fn new(context: GfxContext, width: u32, height: u32, with_ui: bool) -> Gfx {
         let instance = context.instance;
         let surface = context.surface;
+        let errors = Arc::new(Mutex::new(ErrorState::default()));
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
+        let adapter_options = context.adapter_options;
+        let adapter = Gfx::request_adapter(&instance, &surface, &adapter_options)
             .await
             .unwrap();
+        let (device, queue) = Gfx::request_device(&adapter, &errors).await.unwrap();
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    /*features: wgpu::DeviceDescriptor::default().features
-                    | wgpu::Features::POLYGON_MODE_LINE,*/
-                    ..Default::default()
-                },
-                None,
-            )
-            .await
-            .unwrap();
-
-        let format = wgpu::TextureFormat::Bgra8Unorm;
+        // prefer srgb, so scenes can output linear colours
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = capabilities
+            .formats
+            .iter()
+            .copied()
+            .find(|format| format.is_srgb())
+            .or_else(|| capabilities.formats.first().copied())
+            .unwrap_or(wgpu::TextureFormat::Bgra8UnormSrgb);
 
         let surface_desc = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format,
             width,
             height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: PresentMode::Fifo,
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![format],
         };
@@ -93,10 +179,140 @@ impl Gfx {
 
         Gfx {
             config: surface_desc,
+            instance,
+            adapter,
+            adapter_options,
             surface,
+            present_modes: capabilities.present_modes,
             device,
             queue,
             ui,
+            transition: None,
+            errors,
+        }
+    }
+
+    async fn request_adapter(
+        instance: &Instance,
+        surface: &Surface,
+        options: &AdapterOptions,
+    ) -> Result<Adapter, GfxError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(name) = options.name.as_ref() {
+            let search = name.to_lowercase();
+            let adapter = instance
+                .enumerate_adapters(options.backends)
+                .find(|adapter| {
+                    adapter.get_info().name.to_lowercase().contains(&search)
+                        && adapter.is_surface_supported(surface)
+                });
+
+            match adapter {
+                Some(adapter) => return Ok(adapter),
+                None => eprintln!("no adapter matching \"{name}\", using the default"),
+            }
+        }
+
+        instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                compatible_surface: Some(surface),
+                force_fallback_adapter: options.force_fallback,
+            })
+            .await
+            .ok_or(GfxError::NoAdapter)
+    }
+
+    async fn request_device(
+        adapter: &Adapter,
+        errors: &Arc<Mutex<ErrorState>>,
+    ) -> Result<(Device, Queue), GfxError> {
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    /*features: wgpu::DeviceDescriptor::default().features
+                    | wgpu::Features::POLYGON_MODE_LINE,*/
+                    // used to time passes, when supported
+                    features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .map_err(GfxError::RequestDevice)?;
+
+        // wgpu panics on uncaptured errors by default
+        let errors = errors.clone();
+        device.on_uncaptured_error(Box::new(move |error| {
+            let error = match error {
+                wgpu::Error::OutOfMemory { .. } => GfxError::OutOfMemory,
+                // wgpu 0.16 has no device lost callback, it only shows up in validation errors
+                wgpu::Error::Validation { description, .. } => {
+                    if description.contains("device is lost") {
+                        GfxError::DeviceLost
+                    } else {
+                        GfxError::Validation(description)
+                    }
+                }
+            };
+            errors.lock().unwrap().report(error);
+        }));
+
+        Ok((device, queue))
+    }
+
+    // called with errors wgpu couldn't return, instead of logging them
+    pub fn on_error<F: Fn(&GfxError) + Send + 'static>(&mut self, callback: F) {
+        self.errors.lock().unwrap().callback = Some(Box::new(callback));
+    }
+
+    // once lost, nothing renders until recover() succeeds
+    pub fn device_lost(&self) -> bool {
+        self.errors.lock().unwrap().device_lost
+    }
+
+    // requests a new device, falling back to a new adapter if the old one is gone.
+    // scene resources belong to the old device, so they must be rebuilt afterwards
+    pub async fn recover(&mut self) -> Result<(), GfxError> {
+        let (device, queue) = match Gfx::request_device(&self.adapter, &self.errors).await {
+            Ok(device) => device,
+            Err(_) => {
+                self.adapter =
+                    Gfx::request_adapter(&self.instance, &self.surface, &self.adapter_options)
+                        .await?;
+                Gfx::request_device(&self.adapter, &self.errors).await?
+            }
+        };
+
+        self.device = device;
+        self.queue = queue;
+        self.present_modes = self.surface.get_capabilities(&self.adapter).present_modes;
+        if !self.present_modes.contains(&self.config.present_mode) {
+            self.config.present_mode = PresentMode::Fifo;
+        }
+        self.surface.configure(&self.device, &self.config);
+
+        if let Some(ui) = self.ui.as_mut() {
+            ui.rebuild(&self.device, self.config.format);
+        }
+
+        self.transition = None;
+        self.errors.lock().unwrap().device_lost = false;
+
+        Ok(())
+    }
+
+    // falls back to fifo, which every surface supports
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        let present_mode = if self.present_modes.contains(&present_mode) {
+            present_mode
+        } else {
+            PresentMode::Fifo
+        };
+
+        if self.config.present_mode != present_mode {
+            self.config.present_mode = present_mode;
+            self.surface.configure(&self.device, &self.config);
         }
     }
 
@@ -105,6 +321,28 @@ impl Gfx {
         self.config.height = height;
 
         self.surface.configure(&self.device, &self.config);
+
+        if let Some(transition) = self.transition.as_mut() {
+            transition.resize(&self.device, width, height);
+        }
+    }
+
+    // blends from the old scene into the scene passed to render, starting from the next frame.
+    // changing scene during a transition switches straight to the new scene instead
+    pub fn start_transition(&mut self, from: Resources, kind: TransitionKind, duration: Duration) {
+        if self.transition.take().is_some() {
+            return;
+        }
+
+        self.transition = Some(Transition::new(
+            &self.device,
+            from,
+            kind,
+            duration,
+            self.config.format,
+            self.config.width,
+            self.config.height,
+        ));
     }
 
     pub fn render<F: FnOnce(&egui::Context, &Gfx)>(
@@ -115,12 +353,28 @@ impl Gfx {
         ui_input: Option<(f32, egui::RawInput)>, // f32 -> pixels_per_point
         ui_render: F,
     ) -> Option<egui::FullOutput> {
+        if self.device_lost() {
+            return None;
+        }
+
         let frame = match self.surface.get_current_texture() {
             Ok(frame) => frame,
-            Err(e) => {
-                eprintln!("dropped frame: {e:?}");
+            // the window changed under the surface, so configure it again and retry once
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                self.surface.configure(&self.device, &self.config);
+                match self.surface.get_current_texture() {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        eprintln!("dropped frame: {e:?}");
+                        return None;
+                    }
+                }
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                self.errors.lock().unwrap().report(GfxError::OutOfMemory);
                 return None;
             }
+            Err(wgpu::SurfaceError::Timeout) => return None,
         };
 
         let view = frame
@@ -131,8 +385,21 @@ impl Gfx {
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
+        if self
+            .transition
+            .as_ref()
+            .is_some_and(|transition| transition.finished())
+        {
+            self.transition = None;
+        }
+
         if let Some(scene) = scene {
-            scene.render(&self.queue, &view, &mut encoder, time, shadertoy);
+            match self.transition.as_mut() {
+                Some(transition) => {
+                    transition.render(&self.queue, &view, &mut encoder, scene, time, shadertoy)
+                }
+                None => scene.render(&self.queue, &view, &mut encoder, time, shadertoy),
+            }
         }
 
         let mut full_output = None;
@@ -0,0 +1,476 @@
+/*
+ * Post-processing stage, runs a chain of fullscreen effects over a rendered scene
+ */
+use std::collections::HashMap;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, BufferUsages, CommandEncoder, Device, Queue,
+    RenderPipeline, Sampler, ShaderStages, Texture, TextureFormat, TextureView,
+};
+
+use crate::{
+    gfx::buffer::Time,
+    scene::io::{lut::LutContents, post_process::PostEffect},
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct EffectUniform {
+    params: [f32; 4],
+    resolution: [f32; 2],
+    time: f32,
+    _spacer: f32,
+}
+
+// there is no queue while the stage is built, so the table is uploaded on the first frame
+struct LutTexture {
+    texture: Texture,
+    view: TextureView,
+    size: u32,
+    pending: Option<Vec<u8>>,
+}
+
+struct EffectPass {
+    pipeline: RenderPipeline,
+    layout: BindGroupLayout,
+    uniform: Buffer,
+    params: [f32; 4],
+    lut: Option<LutTexture>,
+}
+
+pub struct PostProcess {
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    sampler: Sampler,
+    passes: Vec<EffectPass>,
+    // the scene is drawn into input, then each pass reads the previous pass's target
+    input: TextureView,
+    intermediates: Vec<TextureView>,
+    bind_groups: Vec<BindGroup>,
+}
+
+impl PostProcess {
+    // luts holds the .cube file contents, keyed by the id lut effects refer to
+    pub fn new(
+        device: &Device,
+        effects: &[PostEffect],
+        luts: &HashMap<String, Vec<u8>>,
+        format: TextureFormat,
+        output_format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<PostProcess, String> {
+        let mut pass_descriptors: Vec<(&str, [f32; 4], Option<LutTexture>)> = Vec::new();
+
+        for effect in effects.iter() {
+            match effect {
+                PostEffect::Bloom {
+                    threshold,
+                    intensity,
+                    radius,
+                } => pass_descriptors.push((
+                    "bloom",
+                    [
+                        threshold.unwrap_or(0.8),
+                        intensity.unwrap_or(0.5),
+                        radius.unwrap_or(8.0),
+                        0.0,
+                    ],
+                    None,
+                )),
+                PostEffect::Vignette {
+                    strength,
+                    radius,
+                    softness,
+                } => pass_descriptors.push((
+                    "vignette",
+                    [
+                        strength.unwrap_or(0.5),
+                        radius.unwrap_or(0.5),
+                        softness.unwrap_or(0.5),
+                        0.0,
+                    ],
+                    None,
+                )),
+                PostEffect::Grain { amount } => {
+                    pass_descriptors.push(("grain", [amount.unwrap_or(0.05), 0.0, 0.0, 0.0], None))
+                }
+                PostEffect::Lut { lut, strength } => {
+                    let content = luts
+                        .get(lut)
+                        .ok_or_else(|| format!("Lut {} was not loaded", lut))?;
+                    let contents = LutContents::parse(content)
+                        .map_err(|error| format!("Lut {}: {}", lut, error))?;
+                    let texture = PostProcess::create_lut_texture(device, lut, contents);
+
+                    // tables map srgb colours, so linear colours are encoded for the lookup
+                    let srgb = if format.is_srgb() { 1.0 } else { 0.0 };
+                    pass_descriptors.push((
+                        "lut",
+                        [strength.unwrap_or(1.0), texture.size as f32, srgb, 0.0],
+                        Some(texture),
+                    ));
+                }
+                PostEffect::Blur { radius } => {
+                    // separable, so blur horizontally then vertically
+                    let radius = radius.unwrap_or(4.0);
+                    pass_descriptors.push(("blur", [radius, 0.0, 1.0, 0.0], None));
+                    pass_descriptors.push(("blur", [radius, 0.0, 0.0, 1.0], None));
+                }
+                PostEffect::Gamma { power } => {
+                    pass_descriptors.push(("gamma", [*power, 0.0, 0.0, 0.0], None))
+                }
+            }
+        }
+
+        // an empty chain still has to copy the input into the output
+        if pass_descriptors.is_empty() {
+            pass_descriptors.push(("blit", [0.0; 4], None));
+        }
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Process Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/postprocess.wgsl").into()),
+        });
+
+        let pass_count = pass_descriptors.len();
+        let mut passes = Vec::new();
+
+        for (index, (entry_point, params, lut)) in pass_descriptors.into_iter().enumerate() {
+            let mut layout_entries = vec![
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ];
+            if lut.is_some() {
+                layout_entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D3,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                });
+            }
+
+            let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: layout_entries.as_slice(),
+                label: Some("Post Process Bind Group Layout"),
+            });
+
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post Process Pipeline Layout"),
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+
+            // the last pass draws straight into the output
+            let target_format = if index == pass_count - 1 {
+                output_format
+            } else {
+                format
+            };
+
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(format!("Post Process Pipeline ({})", entry_point).as_str()),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: target_format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Cw,
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            });
+
+            let uniform = device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("Post Process Uniform"),
+                contents: bytemuck::cast_slice(&[EffectUniform {
+                    params,
+                    resolution: [width as f32, height as f32],
+                    time: 0.0,
+                    _spacer: 0.0,
+                }]),
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            });
+
+            passes.push(EffectPass {
+                pipeline,
+                layout,
+                uniform,
+                params,
+                lut,
+            });
+        }
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let mut post_process = PostProcess {
+            format,
+            width,
+            height,
+            sampler,
+            passes,
+            input: PostProcess::create_target(device, "Post Process Input", format, width, height),
+            intermediates: Vec::new(),
+            bind_groups: Vec::new(),
+        };
+        post_process.create_intermediates(device);
+
+        Ok(post_process)
+    }
+
+    // gpu memory held by the input and intermediate targets, the output belongs to the caller
+    pub fn memory_bytes(&self) -> u64 {
+        let pixel_bytes = self.format.block_size(None).unwrap_or(4) as u64;
+        let targets = 1 + self.intermediates.len() as u64;
+        targets * self.width.max(1) as u64 * self.height.max(1) as u64 * pixel_bytes
+    }
+
+    // the view the scene should be rendered into
+    pub fn input(&self) -> &TextureView {
+        &self.input
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.input =
+            PostProcess::create_target(device, "Post Process Input", self.format, width, height);
+        self.create_intermediates(device);
+    }
+
+    fn create_intermediates(&mut self, device: &Device) {
+        // passes ping-pong between two targets, the last pass needs none
+        let count = self.passes.len().saturating_sub(1).min(2);
+        self.intermediates = (0..count)
+            .map(|_| {
+                PostProcess::create_target(
+                    device,
+                    "Post Process Intermediate",
+                    self.format,
+                    self.width,
+                    self.height,
+                )
+            })
+            .collect();
+
+        self.bind_groups = self
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                let source = if index == 0 {
+                    &self.input
+                } else {
+                    &self.intermediates[(index - 1) % 2]
+                };
+
+                let mut entries = vec![
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: pass.uniform.as_entire_binding(),
+                    },
+                ];
+                if let Some(lut) = pass.lut.as_ref() {
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&lut.view),
+                    });
+                }
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &pass.layout,
+                    entries: entries.as_slice(),
+                    label: Some("Post Process Bind Group"),
+                })
+            })
+            .collect();
+    }
+
+    fn create_target(
+        device: &Device,
+        label: &str,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_lut_texture(device: &Device, id: &String, contents: LutContents) -> LutTexture {
+        let size = contents.size;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(format!("Lut {}", id).as_str()),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: size,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        LutTexture {
+            texture,
+            view,
+            size,
+            pending: Some(contents.bytes),
+        }
+    }
+
+    pub fn apply(
+        &mut self,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        output: &TextureView,
+        time: Time,
+    ) {
+        let last = self.passes.len().saturating_sub(1);
+
+        for (index, pass) in self.passes.iter_mut().enumerate() {
+            if let Some(lut) = pass.lut.as_mut() {
+                if let Some(bytes) = lut.pending.take() {
+                    queue.write_texture(
+                        wgpu::ImageCopyTexture {
+                            texture: &lut.texture,
+                            mip_level: 0,
+                            origin: wgpu::Origin3d::ZERO,
+                            aspect: wgpu::TextureAspect::All,
+                        },
+                        bytes.as_slice(),
+                        wgpu::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(4 * lut.size),
+                            rows_per_image: Some(lut.size),
+                        },
+                        wgpu::Extent3d {
+                            width: lut.size,
+                            height: lut.size,
+                            depth_or_array_layers: lut.size,
+                        },
+                    );
+                }
+            }
+
+            queue.write_buffer(
+                &pass.uniform,
+                0,
+                bytemuck::cast_slice(&[EffectUniform {
+                    params: pass.params,
+                    resolution: [self.width as f32, self.height as f32],
+                    time: time.time as f32 / 1000.0,
+                    _spacer: 0.0,
+                }]),
+            );
+
+            let target = if index == last {
+                output
+            } else {
+                &self.intermediates[index % 2]
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Process Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &self.bind_groups[index], &[]);
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * Frame statistics, and gpu timing of each pass using timestamp queries
+ */
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use wgpu::{Buffer, CommandEncoder, Device, QuerySet, Queue};
+
+// frames kept for the history graphs
+pub const HISTORY_FRAMES: usize = 240;
+// timestamps are written before and after each pass
+const MAX_TIMED_PASSES: u32 = 64;
+// readbacks in flight, so reading a frame's times never waits for the gpu
+const READBACK_BUFFERS: usize = 2;
+
+#[derive(Clone, Debug)]
+pub struct PassTime {
+    pub label: String,
+    pub ms: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ResourceMemory {
+    pub label: String,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    // cpu time spent rendering each frame
+    pub frame_ms: VecDeque<f32>,
+    // time between frames, including time spent waiting for the frame limiter
+    pub interval_ms: VecDeque<f32>,
+    // None when the device doesn't support timestamp queries
+    pub passes: Option<Vec<PassTime>>,
+    pub memory: Vec<ResourceMemory>,
+}
+
+impl FrameStats {
+    pub fn record_frame(&mut self, frame_ms: f32, interval_ms: f32) {
+        for (history, value) in [
+            (&mut self.frame_ms, frame_ms),
+            (&mut self.interval_ms, interval_ms),
+        ] {
+            if history.len() >= HISTORY_FRAMES {
+                history.pop_front();
+            }
+            history.push_back(value);
+        }
+    }
+
+    pub fn average_frame_ms(&self) -> f32 {
+        average(&self.frame_ms)
+    }
+
+    pub fn fps(&self) -> f32 {
+        let interval_ms = average(&self.interval_ms);
+        if interval_ms > 0.0 {
+            1000.0 / interval_ms
+        } else {
+            0.0
+        }
+    }
+
+    pub fn memory_bytes(&self) -> u64 {
+        self.memory.iter().map(|memory| memory.bytes).sum()
+    }
+}
+
+fn average(history: &VecDeque<f32>) -> f32 {
+    if history.is_empty() {
+        return 0.0;
+    }
+    history.iter().sum::<f32>() / history.len() as f32
+}
+
+enum ReadbackState {
+    Idle,
+    // copied into by a frame which hasn't been submitted yet
+    Copied,
+    // set by the map callback, to whether the mapping succeeded
+    Mapping(Arc<Mutex<Option<bool>>>),
+}
+
+struct Readback {
+    buffer: Buffer,
+    labels: Vec<String>,
+    frame: u64,
+    state: ReadbackState,
+}
+
+pub struct GpuTimer {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readbacks: Vec<Readback>,
+    // nanoseconds per timestamp tick
+    period: f32,
+    labels: Vec<String>,
+    frame: u64,
+    // the most recent times read back, shown until newer ones arrive
+    times: Vec<PassTime>,
+}
+
+impl GpuTimer {
+    // None when the device wasn't created with timestamp queries
+    pub fn new(device: &Device, queue: &Queue) -> Option<GpuTimer> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let count = MAX_TIMED_PASSES * 2;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timestamps Resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readbacks = (0..READBACK_BUFFERS)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Pass Timestamps Readback"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                labels: Vec::new(),
+                frame: 0,
+                state: ReadbackState::Idle,
+            })
+            .collect();
+
+        Some(GpuTimer {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            period: queue.get_timestamp_period(),
+            labels: Vec::new(),
+            frame: 0,
+            times: Vec::new(),
+        })
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.labels.clear();
+        self.frame += 1;
+    }
+
+    // passes past the limit aren't timed
+    pub fn start(&mut self, encoder: &mut CommandEncoder, label: String) -> bool {
+        let index = self.labels.len() as u32;
+        if index >= MAX_TIMED_PASSES {
+            return false;
+        }
+
+        encoder.write_timestamp(&self.query_set, index * 2);
+        self.labels.push(label);
+        true
+    }
+
+    pub fn end(&mut self, encoder: &mut CommandEncoder) {
+        let index = self.labels.len() as u32 - 1;
+        encoder.write_timestamp(&self.query_set, index * 2 + 1);
+    }
+
+    // frames are skipped while every readback buffer is still in flight
+    pub fn resolve(&mut self, encoder: &mut CommandEncoder) {
+        if self.labels.is_empty() {
+            return;
+        }
+        let readback = match self
+            .readbacks
+            .iter_mut()
+            .find(|readback| matches!(readback.state, ReadbackState::Idle))
+        {
+            Some(readback) => readback,
+            None => return,
+        };
+
+        let count = self.labels.len() as u32 * 2;
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &readback.buffer,
+            0,
+            count as u64 * wgpu::QUERY_SIZE as u64,
+        );
+        readback.labels = self.labels.clone();
+        readback.frame = self.frame;
+        readback.state = ReadbackState::Copied;
+    }
+
+    // call after the frame's commands are submitted
+    // returns the latest finished frame's times, which lag the gpu by a frame or two
+    pub fn read(&mut self, device: &Device) -> Vec<PassTime> {
+        for readback in self.readbacks.iter_mut() {
+            if let ReadbackState::Copied = readback.state {
+                let mapped = Arc::new(Mutex::new(None));
+                let callback_mapped = mapped.clone();
+                let size = readback.labels.len() as u64 * 2 * wgpu::QUERY_SIZE as u64;
+                readback
+                    .buffer
+                    .slice(..size)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        *callback_mapped.lock().unwrap() = Some(result.is_ok());
+                    });
+                readback.state = ReadbackState::Mapping(mapped);
+            }
+        }
+        device.poll(wgpu::MaintainBase::Poll);
+
+        let mut latest = None;
+        for readback in self.readbacks.iter_mut() {
+            let mapped = match &readback.state {
+                ReadbackState::Mapping(mapped) => *mapped.lock().unwrap(),
+                _ => continue,
+            };
+            match mapped {
+                Some(true) => {
+                    let size = readback.labels.len() as u64 * 2 * wgpu::QUERY_SIZE as u64;
+                    let times: Vec<PassTime> = {
+                        let data = readback.buffer.slice(..size).get_mapped_range();
+                        let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                        readback
+                            .labels
+                            .iter()
+                            .zip(timestamps.chunks(2))
+                            .map(|(label, timestamps)| PassTime {
+                                label: label.clone(),
+                                ms: timestamps[1].saturating_sub(timestamps[0]) as f32
+                                    * self.period
+                                    / 1_000_000.0,
+                            })
+                            .collect()
+                    };
+                    readback.buffer.unmap();
+                    readback.state = ReadbackState::Idle;
+
+                    if !matches!(latest, Some((frame, _)) if frame >= readback.frame) {
+                        latest = Some((readback.frame, times));
+                    }
+                }
+                Some(false) => readback.state = ReadbackState::Idle,
+                None => {}
+            }
+        }
+
+        if let Some((_, times)) = latest {
+            self.times = times;
+        }
+        self.times.clone()
+    }
+}
@@ -0,0 +1,53 @@
+/*
+ * Adjusts the render scale to keep frame times near a target
+ */
+use crate::scene::MIN_RENDER_SCALE;
+
+// frames averaged before deciding to change the scale
+const SAMPLE_FRAMES: u32 = 30;
+const SCALE_STEP: f32 = 0.05;
+
+pub struct DynamicResolution {
+    target_ms: f32,
+    total_ms: f32,
+    frames: u32,
+}
+
+impl DynamicResolution {
+    pub fn new(target_fps: f32) -> DynamicResolution {
+        DynamicResolution {
+            target_ms: 1000.0 / target_fps.max(1.0),
+            total_ms: 0.0,
+            frames: 0,
+        }
+    }
+
+    // returns the new scale once enough frames have been timed, if it should change
+    pub fn update(&mut self, frame_ms: f32, scale: f32) -> Option<f32> {
+        self.total_ms += frame_ms;
+        self.frames += 1;
+        if self.frames < SAMPLE_FRAMES {
+            return None;
+        }
+
+        let average_ms = self.total_ms / self.frames as f32;
+        self.total_ms = 0.0;
+        self.frames = 0;
+
+        // scale up more cautiously than down, so the scale doesn't oscillate
+        let new_scale = if average_ms > self.target_ms * 1.1 {
+            scale - SCALE_STEP * 2.0
+        } else if average_ms < self.target_ms * 0.7 {
+            scale + SCALE_STEP
+        } else {
+            return None;
+        };
+
+        let new_scale = new_scale.clamp(MIN_RENDER_SCALE, 1.0);
+        if (new_scale - scale).abs() < f32::EPSILON {
+            return None;
+        }
+
+        Some(new_scale)
+    }
+}
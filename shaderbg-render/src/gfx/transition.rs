@@ -0,0 +1,325 @@
+/*
+ * Transitions between scenes, the old and new scenes are both drawn offscreen and blended
+ * the old scene's resources are kept alive until the transition finishes
+ */
+use std::time::{Duration, Instant};
+
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, BufferUsages, CommandEncoder, Device, Queue,
+    RenderPipeline, Sampler, ShaderStages, TextureFormat, TextureView,
+};
+
+use crate::{
+    gfx::buffer::{ShaderToy, Time},
+    scene::Resources,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionKind {
+    Crossfade,
+    Wipe,
+    Dissolve,
+}
+
+impl TransitionKind {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            Self::Crossfade => "crossfade",
+            Self::Wipe => "wipe",
+            Self::Dissolve => "dissolve",
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BlendUniform {
+    resolution: [f32; 2],
+    progress: f32,
+    _spacer: f32,
+}
+
+pub struct Transition {
+    from: Resources,
+    // started on the first frame, so building the new scene isn't part of the transition
+    started: Option<Instant>,
+    duration: Duration,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    pipeline: RenderPipeline,
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    uniform: Buffer,
+    from_view: TextureView,
+    to_view: TextureView,
+    bind_group: BindGroup,
+}
+
+impl Transition {
+    // format must be the format both scenes were built for
+    pub fn new(
+        device: &Device,
+        from: Resources,
+        kind: TransitionKind,
+        duration: Duration,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Transition {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Transition Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/transition.wgsl").into()),
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Transition Bind Group Layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Transition Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(format!("Transition Pipeline ({})", kind.entry_point()).as_str()),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: kind.entry_point(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let uniform = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Transition Uniform"),
+            contents: bytemuck::cast_slice(&[BlendUniform {
+                resolution: [width as f32, height as f32],
+                progress: 0.0,
+                _spacer: 0.0,
+            }]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let from_view = Transition::create_target(device, "Transition From", format, width, height);
+        let to_view = Transition::create_target(device, "Transition To", format, width, height);
+        let bind_group = Transition::create_bind_group(
+            device, &layout, &from_view, &to_view, &sampler, &uniform,
+        );
+
+        Transition {
+            from,
+            started: None,
+            duration,
+            width,
+            height,
+            format,
+            pipeline,
+            layout,
+            sampler,
+            uniform,
+            from_view,
+            to_view,
+            bind_group,
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        match self.started {
+            Some(started) if !self.duration.is_zero() => {
+                (started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+            }
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.from.resize(device, width, height);
+        self.from_view =
+            Transition::create_target(device, "Transition From", self.format, width, height);
+        self.to_view =
+            Transition::create_target(device, "Transition To", self.format, width, height);
+        self.bind_group = Transition::create_bind_group(
+            device,
+            &self.layout,
+            &self.from_view,
+            &self.to_view,
+            &self.sampler,
+            &self.uniform,
+        );
+    }
+
+    fn create_target(
+        device: &Device,
+        label: &str,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        from_view: &TextureView,
+        to_view: &TextureView,
+        sampler: &Sampler,
+        uniform: &Buffer,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(from_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(to_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform.as_entire_binding(),
+                },
+            ],
+            label: Some("Transition Bind Group"),
+        })
+    }
+
+    // both scenes keep animating while they are blended
+    pub fn render(
+        &mut self,
+        queue: &Queue,
+        output: &TextureView,
+        encoder: &mut CommandEncoder,
+        to: &mut Resources,
+        time: Time,
+        shadertoy: ShaderToy,
+    ) {
+        self.started.get_or_insert_with(Instant::now);
+
+        self.from
+            .render(queue, &self.from_view, encoder, time, shadertoy);
+        to.render(queue, &self.to_view, encoder, time, shadertoy);
+
+        queue.write_buffer(
+            &self.uniform,
+            0,
+            bytemuck::cast_slice(&[BlendUniform {
+                resolution: [self.width as f32, self.height as f32],
+                progress: self.progress(),
+                _spacer: 0.0,
+            }]),
+        );
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Transition Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
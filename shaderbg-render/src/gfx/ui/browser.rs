@@ -1,81 +1,120 @@
+/*
+ * Lists the installed scenes with a preview of each
+ * previews are rendered once into a thumbnail, which is cached as a png when a cache dir is given.
+ * only the hovered or selected scene is animated
+ */
 use egui::RichText;
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
 use wgpu::{
-    BindGroupLayout, CommandEncoder, Device, Queue, RenderPipeline, Sampler, Texture,
-    TextureDescriptor, TextureFormat, TextureView, TextureViewDescriptor,
+    CommandEncoder, Device, Queue, Texture, TextureDescriptor, TextureFormat, TextureView,
+    TextureViewDescriptor,
 };
 
 use crate::{
-    gfx::buffer::{ShaderToy, Time},
-    scene::{io::Metadata, Resources, Scene},
+    gfx::{
+        buffer::{ShaderToy, Time},
+        headless::read_texture,
+    },
+    scene::{io::Metadata, RenderOptions, Resources, Scene},
 };
 
 const PREVIEW_WIDTH: u32 = 128;
 const PREVIEW_HEIGHT: u32 = 72;
-const PREVIEW_FORMAT: TextureFormat = TextureFormat::Bgra8UnormSrgb;
-const PREVIEW_POST_PROCESS_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+// egui expects srgb textures
+const PREVIEW_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+// thumbnails are taken a little way in, as some scenes start blank
+const THUMBNAIL_TIME_MS: u32 = 2000;
+// bumped when previews render differently, so old thumbnails aren't used
+const THUMBNAIL_VERSION: u32 = 1;
 
 pub struct Browser {
     scenes: Box<[(String, Metadata)]>,
     previews: HashMap<String, ScenePreview>,
-    preview_post_process: ScenePreviewPostProcess,
     preview_shadertoy: ShaderToy,
+    hovered: Option<String>,
+    selected: Option<String>,
 }
 
 impl Browser {
-    pub fn new(scenes: Vec<(String, &Scene)>, device: &Device) -> Browser {
+    pub fn new(scenes: Vec<(String, &Scene)>, cache_dir: Option<&Path>) -> Browser {
         let mut previews = HashMap::new();
 
         for (name, scene) in scenes.iter() {
-            previews.insert(name.clone(), ScenePreview::new(scene, device, name));
+            let cache_path = cache_dir.map(|cache_dir| {
+                cache_dir.join(format!("{}-{:016x}.png", name, scene_hash(name, scene)))
+            });
+            previews.insert(name.clone(), ScenePreview::new(cache_path));
         }
 
-        let preview_post_process = ScenePreviewPostProcess::new(device);
-
         Browser {
             scenes: scenes
                 .iter()
                 .map(|(name, scene)| (name.clone(), scene.descriptor.meta.clone()))
                 .collect(),
             previews,
-            preview_post_process,
             preview_shadertoy: ShaderToy::new(),
+            hovered: None,
+            selected: None,
         }
     }
 
-    pub fn update_previews(
+    // scene looks up a scene by name, scene resources are only created when a preview needs them
+    pub fn update_previews<'a>(
         &mut self,
         renderer: &mut egui_wgpu::Renderer,
         queue: &Queue,
         device: &mut Device,
         time: Time,
+        scene: impl Fn(&str) -> Option<&'a Scene>,
     ) {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Preview Encoder"),
         });
         self.preview_shadertoy
             .update(time.time, time.dt as f64, PREVIEW_WIDTH, PREVIEW_HEIGHT);
-        for (_, preview) in self.previews.iter_mut() {
-            preview.render(
-                renderer,
-                queue,
-                device,
-                &mut encoder,
-                time,
-                &self.preview_post_process,
-                self.preview_shadertoy,
-            );
+        for (name, preview) in self.previews.iter_mut() {
+            if preview.failed {
+                continue;
+            }
+
+            if preview.thumbnail.is_none() {
+                preview.create_thumbnail(renderer, queue, device, || scene(name));
+            }
+
+            let active =
+                self.hovered.as_ref() == Some(name) || self.selected.as_ref() == Some(name);
+            if active {
+                preview.render(
+                    renderer,
+                    queue,
+                    device,
+                    &mut encoder,
+                    time,
+                    self.preview_shadertoy,
+                    || scene(name),
+                );
+            }
         }
         queue.submit(Some(encoder.finish()));
     }
 
+    // the name of a scene returned by render
+    pub fn scene_name(&self, index: usize) -> Option<&str> {
+        self.scenes.get(index).map(|(name, _)| name.as_str())
+    }
+
     pub fn render(
-        &self,
+        &mut self,
         ui: &mut egui::Ui,
         current_scene: Option<usize>,
         reload: Option<&mut bool>,
     ) -> Option<usize> {
         let mut selected = None;
+        let mut hovered = None;
         ui.horizontal(|ui| {
             ui.heading(format!("Scenes loaded: {}", self.scenes.len()));
             if let Some(reload) = reload {
@@ -89,14 +128,21 @@ impl Browser {
         ui.separator();
         for (index, (name, meta)) in self.scenes.iter().enumerate() {
             let preview = self.previews.get(name);
+            let active = self.hovered.as_ref() == Some(name) || Some(index) == current_scene;
+            let size = egui::Vec2::new(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32);
 
-            ui.horizontal(|ui| {
-                if let Some(preview) = preview {
-                    if let Some(texture) = preview.egui_texture.as_ref() {
-                        ui.image(
-                            *texture,
-                            egui::Vec2::new(PREVIEW_WIDTH as f32, PREVIEW_HEIGHT as f32),
-                        );
+            let row = ui.horizontal(|ui| {
+                match preview.and_then(|preview| preview.texture_id(active)) {
+                    Some(texture) => {
+                        ui.image(texture, size);
+                    }
+                    None => {
+                        let label = if preview.map(|preview| preview.failed).unwrap_or(false) {
+                            "No preview"
+                        } else {
+                            "Loading..."
+                        };
+                        ui.add_sized(size, egui::Label::new(label));
                     }
                 }
 
@@ -114,234 +160,309 @@ impl Browser {
                     }
                 })
             });
+
+            if row.response.hovered() {
+                hovered = Some(name.clone());
+            }
         }
+
+        self.hovered = hovered;
+        // the index comes from the app, which can be ahead of the browser after a rescan
+        self.selected = current_scene
+            .and_then(|index| self.scenes.get(index))
+            .map(|(name, _)| name.clone());
+
         selected
     }
 }
 
+// changes when the scene is updated or its settings are changed, so stale thumbnails aren't used
+fn scene_hash(name: &str, scene: &Scene) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    THUMBNAIL_VERSION.hash(&mut hasher);
+    name.hash(&mut hasher);
+    scene.descriptor.meta.version.hash(&mut hasher);
+
+    let mut settings: Vec<_> = scene.settings.iter().collect();
+    settings.sort_by_key(|(key, _)| *key);
+    for (key, value) in settings {
+        key.hash(&mut hasher);
+        format!("{:?}", value).hash(&mut hasher);
+    }
+
+    let mut files: Vec<_> = scene.files.iter().collect();
+    files.sort_by_key(|(file, _)| *file);
+    for (file, contents) in files {
+        file.hash(&mut hasher);
+        contents.hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+fn create_preview_texture(device: &Device, label: &str, usage: wgpu::TextureUsages) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: PREVIEW_WIDTH,
+            height: PREVIEW_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: PREVIEW_FORMAT,
+        usage,
+        view_formats: &[PREVIEW_FORMAT],
+    })
+}
+
+fn create_preview_view(texture: &Texture) -> TextureView {
+    texture.create_view(&TextureViewDescriptor {
+        label: Some("Scene Preview Texture View"),
+        format: Some(PREVIEW_FORMAT),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        ..Default::default()
+    })
+}
+
+struct Thumbnail {
+    // egui only holds a view, so the texture is kept here
+    _texture: Texture,
+    egui_texture: egui::epaint::TextureId,
+}
+
 struct ScenePreview {
-    resources: Resources,
-    texture: Texture,
-    texture_out: Texture,
-    egui_texture: Option<egui::epaint::TextureId>,
+    cache_path: Option<PathBuf>,
+    thumbnail: Option<Thumbnail>,
+    live: Option<LivePreview>,
+    // the scene couldn't be rendered, so no more attempts are made
+    failed: bool,
 }
 
 impl ScenePreview {
-    pub fn new(scene: &Scene, device: &Device, name: &String) -> ScenePreview {
+    fn new(cache_path: Option<PathBuf>) -> ScenePreview {
         ScenePreview {
-            resources: Resources::new(scene, device, PREVIEW_WIDTH, PREVIEW_HEIGHT, PREVIEW_FORMAT)
-                .unwrap(),
-            texture: device.create_texture(&TextureDescriptor {
-                label: Some(format!("Scene Preview {}", name).as_str()),
-                size: wgpu::Extent3d {
+            cache_path,
+            thumbnail: None,
+            live: None,
+            failed: false,
+        }
+    }
+
+    fn texture_id(&self, active: bool) -> Option<egui::epaint::TextureId> {
+        let live = self.live.as_ref().and_then(|live| live.egui_texture);
+        let thumbnail = self
+            .thumbnail
+            .as_ref()
+            .map(|thumbnail| thumbnail.egui_texture);
+
+        if active {
+            live.or(thumbnail)
+        } else {
+            thumbnail
+        }
+    }
+
+    fn live<'a>(
+        &mut self,
+        device: &Device,
+        scene: impl FnOnce() -> Option<&'a Scene>,
+    ) -> Option<&mut LivePreview> {
+        if self.live.is_none() {
+            let scene = scene()?;
+            match LivePreview::new(scene, device) {
+                Ok(live) => self.live = Some(live),
+                Err(error) => {
+                    eprintln!(
+                        "Failed to create preview for {}: {}",
+                        scene.descriptor.meta.name, error
+                    );
+                    self.failed = true;
+                }
+            }
+        }
+
+        self.live.as_mut()
+    }
+
+    fn create_thumbnail<'a>(
+        &mut self,
+        renderer: &mut egui_wgpu::Renderer,
+        queue: &Queue,
+        device: &mut Device,
+        scene: impl FnOnce() -> Option<&'a Scene>,
+    ) {
+        let texture = create_preview_texture(
+            device,
+            "Scene Thumbnail",
+            wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+        );
+
+        let cached = self
+            .cache_path
+            .as_ref()
+            .and_then(|path| image::open(path).ok())
+            .map(|image| image.to_rgba8())
+            .filter(|image| image.dimensions() == (PREVIEW_WIDTH, PREVIEW_HEIGHT));
+
+        if let Some(image) = cached {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(PREVIEW_WIDTH * 4),
+                    rows_per_image: Some(PREVIEW_HEIGHT),
+                },
+                wgpu::Extent3d {
                     width: PREVIEW_WIDTH,
                     height: PREVIEW_HEIGHT,
                     depth_or_array_layers: 1,
                 },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: PREVIEW_FORMAT,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[PREVIEW_FORMAT],
-            }),
-            texture_out: device.create_texture(&TextureDescriptor {
-                label: Some(format!("Scene Preview {}", name).as_str()),
-                size: wgpu::Extent3d {
+            );
+        } else {
+            let live = match self.live(device, scene) {
+                Some(live) => live,
+                None => return,
+            };
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Thumbnail Encoder"),
+            });
+            let mut time = Time::new();
+            time.update_time(THUMBNAIL_TIME_MS, 0.0);
+            let mut shadertoy = ShaderToy::new();
+            shadertoy.update(time.time, 0.0, PREVIEW_WIDTH, PREVIEW_HEIGHT);
+            live.render(renderer, queue, device, &mut encoder, time, shadertoy);
+
+            encoder.copy_texture_to_texture(
+                live.texture.as_image_copy(),
+                texture.as_image_copy(),
+                wgpu::Extent3d {
                     width: PREVIEW_WIDTH,
                     height: PREVIEW_HEIGHT,
                     depth_or_array_layers: 1,
                 },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: PREVIEW_POST_PROCESS_FORMAT,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[PREVIEW_POST_PROCESS_FORMAT],
-            }),
-            egui_texture: None,
+            );
+            queue.submit(Some(encoder.finish()));
+
+            if let Some(path) = self.cache_path.as_ref() {
+                if let Err(error) = save_thumbnail(device, queue, &texture, path) {
+                    eprintln!("Failed to cache thumbnail {:?}: {}", path, error);
+                }
+            }
         }
+
+        let egui_texture = renderer.register_native_texture(
+            device,
+            &create_preview_view(&texture),
+            wgpu::FilterMode::Linear,
+        );
+        self.thumbnail = Some(Thumbnail {
+            _texture: texture,
+            egui_texture,
+        });
     }
 
     #[allow(clippy::too_many_arguments)] // todo: cleanup args, probably take Gfx
-    pub fn render(
+    fn render<'a>(
         &mut self,
         renderer: &mut egui_wgpu::Renderer,
         queue: &Queue,
         device: &mut Device,
         encoder: &mut CommandEncoder,
         time: Time,
-        post_process: &ScenePreviewPostProcess,
         shadertoy: ShaderToy,
+        scene: impl FnOnce() -> Option<&'a Scene>,
     ) {
-        let view = self.texture.create_view(&TextureViewDescriptor {
-            label: Some("Scene Preview Texture View"),
-            format: Some(PREVIEW_FORMAT),
-            dimension: Some(wgpu::TextureViewDimension::D2),
-            ..Default::default()
-        });
-        self.resources
-            .render(queue, &view, encoder, time, shadertoy);
-
-        let view_out = self.texture_out.create_view(&TextureViewDescriptor {
-            label: Some("Scene Preview Post Process Texture View"),
-            format: Some(PREVIEW_POST_PROCESS_FORMAT),
-            dimension: Some(wgpu::TextureViewDimension::D2),
-            ..Default::default()
-        });
-        post_process.apply(&view, &view_out, encoder, device);
-        if let Some(texture) = self.egui_texture.as_ref() {
-            renderer.update_egui_texture_from_wgpu_texture(
-                device,
-                &view_out,
-                wgpu::FilterMode::Linear,
-                *texture,
-            );
-        } else {
-            self.egui_texture =
-                Some(renderer.register_native_texture(device, &view_out, wgpu::FilterMode::Linear));
+        if let Some(live) = self.live(device, scene) {
+            live.render(renderer, queue, device, encoder, time, shadertoy);
         }
     }
 }
 
-struct ScenePreviewPostProcess {
-    pipeline: RenderPipeline,
-    texture_sampler: Sampler,
-    bind_group_layout: BindGroupLayout,
-}
-
-impl ScenePreviewPostProcess {
-    pub fn new(device: &Device) -> ScenePreviewPostProcess {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Scene Preview Post Process Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/postprocess.wgsl").into()),
-        });
-
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-            label: Some("Scene Preview Post Process Bind Group Layout"),
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Scene Preview Post Process Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
-            push_constant_ranges: &[],
-        });
+fn save_thumbnail(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    path: &Path,
+) -> Result<(), String> {
+    let pixels = read_texture(device, queue, texture, PREVIEW_WIDTH, PREVIEW_HEIGHT)
+        .map_err(|error| format!("{:?}", error))?;
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Scene Preview Post Process Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "postprocess_to_srgb",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: PREVIEW_POST_PROCESS_FORMAT,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Cw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    image::save_buffer(
+        path,
+        &pixels,
+        PREVIEW_WIDTH,
+        PREVIEW_HEIGHT,
+        image::ColorType::Rgba8,
+    )
+    .map_err(|error| error.to_string())
+}
 
-        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+struct LivePreview {
+    resources: Resources,
+    texture: Texture,
+    egui_texture: Option<egui::epaint::TextureId>,
+}
 
-        ScenePreviewPostProcess {
-            pipeline,
-            texture_sampler,
-            bind_group_layout,
-        }
+impl LivePreview {
+    fn new(scene: &Scene, device: &Device) -> Result<LivePreview, String> {
+        Ok(LivePreview {
+            resources: Resources::new(
+                scene,
+                device,
+                PREVIEW_WIDTH,
+                PREVIEW_HEIGHT,
+                PREVIEW_FORMAT,
+                &RenderOptions::default(),
+            )
+            .map_err(|error| format!("{:?}", error))?,
+            texture: create_preview_texture(
+                device,
+                format!("Scene Preview {}", scene.descriptor.meta.name).as_str(),
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC,
+            ),
+            egui_texture: None,
+        })
     }
 
-    pub fn apply(
-        &self,
-        view: &TextureView,
-        view_out: &TextureView,
+    fn render(
+        &mut self,
+        renderer: &mut egui_wgpu::Renderer,
+        queue: &Queue,
+        device: &Device,
         encoder: &mut CommandEncoder,
-        device: &mut Device,
+        time: Time,
+        shadertoy: ShaderToy,
     ) {
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.texture_sampler),
-                },
-            ],
-            label: Some("diffuse_bind_group"),
-        });
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Post Process Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: view_out,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 0.0,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            });
+        let view_out = create_preview_view(&self.texture);
+        self.resources
+            .render(queue, &view_out, encoder, time, shadertoy);
 
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &bind_group, &[]);
-            render_pass.draw(0..6, 0..1);
+        if let Some(texture) = self.egui_texture.as_ref() {
+            renderer.update_egui_texture_from_wgpu_texture(
+                device,
+                &view_out,
+                wgpu::FilterMode::Linear,
+                *texture,
+            );
+        } else {
+            self.egui_texture =
+                Some(renderer.register_native_texture(device, &view_out, wgpu::FilterMode::Linear));
         }
     }
 }
@@ -1,7 +1,7 @@
 mod browser;
 mod scene;
 pub use browser::Browser;
-pub use scene::{Scene, SceneUiResult};
+pub use scene::{PresetAction, Scene, SceneUiResult};
 
 use egui::{Context, FullOutput, RawInput};
 use egui_wgpu::{renderer::ScreenDescriptor, Renderer};
@@ -23,6 +23,18 @@ impl Ui {
         }
     }
 
+    // renderers belong to a device, so this is needed after the device is lost.
+    // egui only uploads its textures once, so the context is replaced too, keeping its state
+    pub fn rebuild(&mut self, device: &Device, format: TextureFormat) {
+        let context = Context::default();
+        context.set_style(self.context.style());
+        let memory = self.context.memory(|memory| memory.clone());
+        context.memory_mut(|current| *current = memory);
+
+        self.context = context;
+        self.renderer = Renderer::new(device, format, None, 1);
+    }
+
     pub fn context(&self) -> &Context {
         &self.context
     }
@@ -10,7 +10,7 @@ use crate::scene::{
         setting::Setting as SettingDescriptor, setting::SettingValue, util::DeserializableMap,
         Descriptor, Ui as UiDescriptor,
     },
-    Settings,
+    Presets, Settings,
 };
 
 enum SceneUiElement {
@@ -26,10 +26,20 @@ pub enum SceneUiResult {
     Saved,
 }
 
+// changes to the user's presets, which the caller saves
+#[derive(Debug)]
+pub enum PresetAction {
+    Save(String),
+    Rename(String, String),
+    Delete(String),
+}
+
 pub struct Scene {
     setting_descriptors: DeserializableMap<SettingDescriptor>,
     elements: Vec<SceneUiElement>,
     original_values: HashMap<String, SettingValue>,
+    selected_preset: Option<String>,
+    preset_name: String,
 }
 
 impl Scene {
@@ -59,6 +69,8 @@ impl Scene {
             setting_descriptors: descriptor.settings.clone(),
             elements,
             original_values,
+            selected_preset: None,
+            preset_name: String::new(),
         }
     }
 
@@ -94,14 +106,101 @@ impl Scene {
         change
     }
 
+    fn render_bool(ui: &mut egui::Ui, label: &String, mut value: bool) -> Option<SettingValue> {
+        let mut change = None;
+        ui.label(label);
+        if ui.checkbox(&mut value, "").changed() {
+            change = Some(SettingValue::Bool(value));
+        }
+        ui.end_row();
+
+        change
+    }
+
+    // without actions, presets can only be applied
+    fn render_presets(
+        &mut self,
+        ui: &mut egui::Ui,
+        presets: &Presets,
+        changes: &mut Vec<(String, SettingValue)>,
+        actions: Option<&mut Vec<PresetAction>>,
+    ) {
+        let mut selected = None;
+        ui.horizontal(|ui| {
+            ui.label("Preset");
+            egui::ComboBox::from_id_source("scene_preset_combo")
+                .selected_text(self.selected_preset.as_deref().unwrap_or("Custom"))
+                .show_ui(ui, |ui| {
+                    for preset in presets.iter() {
+                        let current = self.selected_preset.as_ref() == Some(&preset.name);
+                        if ui.selectable_label(current, preset.name.as_str()).clicked() {
+                            selected = Some(preset.name.clone());
+                        }
+                    }
+                });
+        });
+        if let Some(selected) = selected {
+            changes.extend(presets.changes(&selected).unwrap_or_default());
+            self.preset_name = selected.clone();
+            self.selected_preset = Some(selected);
+        }
+
+        let actions = match actions {
+            Some(actions) => actions,
+            None => return,
+        };
+
+        let user_preset = self
+            .selected_preset
+            .as_ref()
+            .and_then(|selected| presets.get(selected))
+            .filter(|preset| !preset.builtin)
+            .map(|preset| preset.name.clone());
+        let name = self.preset_name.trim().to_string();
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.preset_name);
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save preset"))
+                .clicked()
+            {
+                actions.push(PresetAction::Save(name.clone()));
+                self.selected_preset = Some(name.clone());
+            }
+            if let Some(preset) = user_preset {
+                if ui
+                    .add_enabled(
+                        !name.is_empty() && name != preset,
+                        egui::Button::new("Rename"),
+                    )
+                    .clicked()
+                {
+                    actions.push(PresetAction::Rename(preset.clone(), name.clone()));
+                    self.selected_preset = Some(name.clone());
+                }
+                if ui.button("Delete").clicked() {
+                    actions.push(PresetAction::Delete(preset));
+                    self.selected_preset = None;
+                }
+            }
+        });
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
         scene_settings: &Settings,
+        presets: &Presets,
         changes: &mut Vec<(String, SettingValue)>,
+        preset_actions: Option<&mut Vec<PresetAction>>,
     ) -> SceneUiResult {
         let mut result = SceneUiResult::Open;
 
+        if !presets.is_empty() || preset_actions.is_some() {
+            self.render_presets(ui, presets, changes, preset_actions);
+            ui.separator();
+        }
+
         let mut group_count = 0;
 
         for element in self.elements.iter() {
@@ -135,6 +234,16 @@ impl Scene {
 
                                         Scene::render_float(ui, label, value, *min, *max)
                                     }
+                                    SettingDescriptor::Bool { label, .. } => {
+                                        let value = {
+                                            match scene_settings.get(key).unwrap() {
+                                                SettingValue::Bool(value) => *value,
+                                                _ => panic!("Setting type mismatch in Ui"),
+                                            }
+                                        };
+
+                                        Scene::render_bool(ui, label, value)
+                                    }
                                 };
 
                                 if let Some(change) = change {
@@ -0,0 +1,145 @@
+/*
+ * Reads buffer contents from files in the scene folder
+ * binary files are raw little-endian elements, csv and json files are rows of numbers
+ */
+use super::resource::{BufferElement, BufferFormat};
+
+pub struct BufferContents {
+    pub bytes: Vec<u8>,
+    // number of elements per row, if the file was split into rows
+    pub row_length: Option<usize>,
+}
+
+impl BufferElement {
+    pub fn size(&self) -> usize {
+        4
+    }
+
+    fn push(&self, bytes: &mut Vec<u8>, value: f64) -> Result<(), String> {
+        match self {
+            BufferElement::F32 => bytes.extend_from_slice(&(value as f32).to_le_bytes()),
+            BufferElement::U32 => {
+                if value < 0.0 || value > u32::MAX as f64 || value.fract() != 0.0 {
+                    return Err(format!("{} is not a valid u32", value));
+                }
+                bytes.extend_from_slice(&(value as u32).to_le_bytes())
+            }
+            BufferElement::I32 => {
+                if value < i32::MIN as f64 || value > i32::MAX as f64 || value.fract() != 0.0 {
+                    return Err(format!("{} is not a valid i32", value));
+                }
+                bytes.extend_from_slice(&(value as i32).to_le_bytes())
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BufferContents {
+    pub fn parse(
+        content: &[u8],
+        format: &BufferFormat,
+        element: BufferElement,
+    ) -> Result<BufferContents, String> {
+        let rows = match format {
+            BufferFormat::Binary => {
+                if content.is_empty() {
+                    return Err("File contains no values".to_string());
+                }
+                if !content.chunks_exact(element.size()).remainder().is_empty() {
+                    return Err(format!(
+                        "File length {} is not a multiple of the element size {}",
+                        content.len(),
+                        element.size()
+                    ));
+                }
+                // wgpu expects little-endian data, which is how the file is stored
+                return Ok(BufferContents {
+                    bytes: content.to_vec(),
+                    row_length: None,
+                });
+            }
+            BufferFormat::Csv => BufferContents::parse_csv(content)?,
+            BufferFormat::Json => BufferContents::parse_json(content)?,
+        };
+
+        if rows.is_empty() {
+            return Err("File contains no values".to_string());
+        }
+
+        let row_length = rows[0].len();
+        if !rows.iter().all(|row| row.len() == row_length) {
+            return Err("Rows must all be the same size".to_string());
+        }
+
+        let mut bytes = Vec::with_capacity(rows.len() * row_length * element.size());
+        for value in rows.iter().flatten() {
+            element.push(&mut bytes, *value)?;
+        }
+
+        Ok(BufferContents {
+            bytes,
+            row_length: Some(row_length),
+        })
+    }
+
+    fn parse_csv(content: &[u8]) -> Result<Vec<Vec<f64>>, String> {
+        let content = std::str::from_utf8(content).map_err(|error| error.to_string())?;
+        let mut rows = Vec::new();
+        let mut first = true;
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let values: Vec<_> = line
+                .split(',')
+                .map(|value| value.trim().parse::<f64>())
+                .collect();
+
+            // allow a header row with column names, after any comments
+            // a row with any number in it is data, so mistakes in it are reported
+            if std::mem::replace(&mut first, false) && values.iter().all(|value| value.is_err()) {
+                continue;
+            }
+
+            let row: Result<Vec<f64>, _> = values.into_iter().collect();
+            match row {
+                Ok(row) => rows.push(row),
+                Err(error) => return Err(format!("Line {}: {}", line_number + 1, error)),
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn parse_json(content: &[u8]) -> Result<Vec<Vec<f64>>, String> {
+        let value: serde_json::Value =
+            serde_json::from_slice(content).map_err(|error| error.to_string())?;
+
+        let number = |value: &serde_json::Value| {
+            value
+                .as_f64()
+                .ok_or_else(|| format!("Expected a number, found {}", value))
+        };
+
+        let array = value
+            .as_array()
+            .ok_or_else(|| "Expected an array of numbers or arrays".to_string())?;
+
+        // an array of numbers is treated as a single row per value
+        if array.iter().all(|value| value.is_number()) {
+            return array.iter().map(|value| Ok(vec![number(value)?])).collect();
+        }
+
+        array
+            .iter()
+            .map(|row| match row.as_array() {
+                Some(row) => row.iter().map(number).collect(),
+                None => Err(format!("Expected an array, found {}", row)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,258 @@
+/*
+ * Numeric expressions over the output resolution and scene settings
+ * e.g. workgroups = ["ceil(width / 8)", "ceil(height / 8)", 1]
+ */
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Expression {
+    Constant(f64),
+    Source(String),
+}
+
+#[derive(Debug)]
+pub enum ExpressionError {
+    UnexpectedToken { source: String, position: usize },
+    UnexpectedEnd { source: String },
+    UnknownVariable(String),
+    UnknownFunction(String),
+    InvalidArguments { function: String, expected: usize },
+}
+
+impl Expression {
+    pub fn evaluate(&self, variables: &HashMap<String, f64>) -> Result<f64, ExpressionError> {
+        match self {
+            Expression::Constant(value) => Ok(*value),
+            Expression::Source(source) => {
+                let mut parser = Parser {
+                    source,
+                    tokens: tokenize(source)?,
+                    position: 0,
+                    variables,
+                };
+                let value = parser.expression()?;
+                if let Some((position, _)) = parser.tokens.get(parser.position) {
+                    return Err(ExpressionError::UnexpectedToken {
+                        source: source.clone(),
+                        position: *position,
+                    });
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            match number.parse::<f64>() {
+                Ok(number) => tokens.push((start, Token::Number(number))),
+                Err(_) => {
+                    return Err(ExpressionError::UnexpectedToken {
+                        source: source.to_string(),
+                        position: start,
+                    })
+                }
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((start, Token::Identifier(chars[start..i].iter().collect())));
+        } else {
+            let token = match c {
+                '+' | '-' | '*' | '/' | '%' | '^' => Token::Operator(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                _ => {
+                    return Err(ExpressionError::UnexpectedToken {
+                        source: source.to_string(),
+                        position: i,
+                    })
+                }
+            };
+            tokens.push((i, token));
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    variables: &'a HashMap<String, f64>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, ExpressionError> {
+        match self.tokens.get(self.position) {
+            Some((_, token)) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(ExpressionError::UnexpectedEnd {
+                source: self.source.to_string(),
+            }),
+        }
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.tokens.get(self.position.saturating_sub(1)) {
+            Some((position, _)) => ExpressionError::UnexpectedToken {
+                source: self.source.to_string(),
+                position: *position,
+            },
+            None => ExpressionError::UnexpectedEnd {
+                source: self.source.to_string(),
+            },
+        }
+    }
+
+    // expression = term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.term()?;
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    // term = unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.unary()?;
+        while let Some(Token::Operator(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.position += 1;
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    // unary = '-' unary | power
+    fn unary(&mut self) -> Result<f64, ExpressionError> {
+        if let Some(Token::Operator('-')) = self.peek() {
+            self.position += 1;
+            return Ok(-self.unary()?);
+        }
+        self.power()
+    }
+
+    // power = primary ('^' unary)?
+    fn power(&mut self) -> Result<f64, ExpressionError> {
+        let base = self.primary()?;
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.position += 1;
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    // primary = number | variable | function '(' arguments ')' | '(' expression ')'
+    fn primary(&mut self) -> Result<f64, ExpressionError> {
+        match self.next()? {
+            Token::Number(number) => Ok(number),
+            Token::Open => {
+                let value = self.expression()?;
+                match self.next()? {
+                    Token::Close => Ok(value),
+                    _ => Err(self.unexpected()),
+                }
+            }
+            Token::Identifier(name) => {
+                if let Some(Token::Open) = self.peek() {
+                    self.position += 1;
+                    let arguments = self.arguments()?;
+                    call(&name, &arguments)
+                } else if let Some(value) = self.variables.get(&name) {
+                    Ok(*value)
+                } else {
+                    Err(ExpressionError::UnknownVariable(name))
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Vec<f64>, ExpressionError> {
+        let mut arguments = Vec::new();
+        if let Some(Token::Close) = self.peek() {
+            self.position += 1;
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.expression()?);
+            match self.next()? {
+                Token::Comma => continue,
+                Token::Close => return Ok(arguments),
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+}
+
+fn call(function: &str, arguments: &[f64]) -> Result<f64, ExpressionError> {
+    let expect = |expected: usize| {
+        if arguments.len() == expected {
+            Ok(())
+        } else {
+            Err(ExpressionError::InvalidArguments {
+                function: function.to_string(),
+                expected,
+            })
+        }
+    };
+
+    match function {
+        "ceil" => expect(1).map(|_| arguments[0].ceil()),
+        "floor" => expect(1).map(|_| arguments[0].floor()),
+        "round" => expect(1).map(|_| arguments[0].round()),
+        "abs" => expect(1).map(|_| arguments[0].abs()),
+        "sqrt" => expect(1).map(|_| arguments[0].sqrt()),
+        "min" => expect(2).map(|_| arguments[0].min(arguments[1])),
+        "max" => expect(2).map(|_| arguments[0].max(arguments[1])),
+        "pow" => expect(2).map(|_| arguments[0].powf(arguments[1])),
+        "clamp" => expect(3).map(|_| arguments[0].max(arguments[1]).min(arguments[2])),
+        _ => Err(ExpressionError::UnknownFunction(function.to_string())),
+    }
+}
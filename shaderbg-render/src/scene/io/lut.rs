@@ -0,0 +1,65 @@
+/*
+ * Reads colour grading tables in the .cube format
+ * entries are listed with red changing fastest, which matches a 3d texture's layout
+ */
+
+pub struct LutContents {
+    pub size: u32,
+    // rgba8 texels, size * size * size of them
+    pub bytes: Vec<u8>,
+}
+
+impl LutContents {
+    pub fn parse(content: &[u8]) -> Result<LutContents, String> {
+        let content = std::str::from_utf8(content).map_err(|error| error.to_string())?;
+        let mut size = None;
+        let mut bytes = Vec::new();
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("LUT_3D_SIZE") {
+                match value.trim().parse::<u32>() {
+                    Ok(value) if value >= 2 => size = Some(value),
+                    _ => return Err(format!("Line {}: invalid LUT_3D_SIZE", line_number + 1)),
+                }
+                continue;
+            }
+
+            // TITLE, DOMAIN_MIN, DOMAIN_MAX etc. don't affect the table
+            if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
+            }
+
+            let entry: Result<Vec<f32>, _> = line
+                .split_whitespace()
+                .map(|value| value.parse::<f32>())
+                .collect();
+
+            match entry {
+                Ok(entry) if entry.len() == 3 => {
+                    for value in entry {
+                        bytes.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    }
+                    bytes.push(255);
+                }
+                _ => return Err(format!("Line {}: expected three numbers", line_number + 1)),
+            }
+        }
+
+        let size = size.ok_or_else(|| "Missing LUT_3D_SIZE".to_string())?;
+        let entries = (size * size * size) as usize;
+        if bytes.len() != entries * 4 {
+            return Err(format!(
+                "Expected {} entries, found {}",
+                entries,
+                bytes.len() / 4
+            ));
+        }
+
+        Ok(LutContents { size, bytes })
+    }
+}
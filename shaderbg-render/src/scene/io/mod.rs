@@ -4,22 +4,38 @@
 
 use serde::Deserialize;
 
+pub mod buffer;
+pub mod expression;
+pub mod lut;
 pub mod pass;
+pub mod post_process;
+pub mod preset;
 pub mod resource;
 pub mod setting;
 pub mod util;
 use pass::*;
+use post_process::*;
 use resource::*;
 use setting::*;
 use util::*;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct Descriptor {
     pub meta: Metadata,
     pub settings: DeserializableMap<Setting>,
     pub ui: Vec<Ui>,
     pub resources: DeserializableMap<Resource>,
     pub render_passes: Vec<RenderPass>,
+    pub simulation: Option<Simulation>,
+    pub post_process: Option<Vec<PostEffect>>,
+    #[serde(default)]
+    pub presets: Vec<preset::Preset>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Simulation {
+    // compute steps to run before the first frame is displayed
+    pub warmup: Option<u32>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -28,9 +44,11 @@ pub struct Metadata {
     pub version: String,
     pub description: String,
     pub author: String,
+    // scenes that change slowly can cap their frame rate
+    pub max_fps: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Ui {
     Setting { setting: String },
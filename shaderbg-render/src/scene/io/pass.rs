@@ -1,39 +1,142 @@
+use hex_color::{HexColor, ParseHexColorError};
 use serde::Deserialize;
-use wgpu::{Face, FrontFace, PolygonMode, PrimitiveTopology, ShaderStages};
+use wgpu::{
+    BlendComponent, BlendFactor, BlendOperation, BlendState, Face, FrontFace, PolygonMode,
+    PrimitiveTopology, ShaderStages,
+};
 
-use super::resource::BufferVertex;
+use super::{expression::Expression, resource::BufferVertex, setting::srgb_to_linear};
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum RenderPass {
     Compute {
         label: Option<String>,
         pipeline: ComputePipeline,
-        workgroups: [u32; 3],
+        workgroups: [Expression; 3],
+        #[serde(flatten)]
+        schedule: PassSchedule,
     },
     Render {
         label: Option<String>,
         pipeline: RenderPipeline,
         clear: Option<RenderClear>,
         draw: Vec<RenderDraw>,
+        #[serde(flatten)]
+        schedule: PassSchedule,
     },
     ShaderToy {
         label: Option<String>,
         source: String,
-        bindings: Option<Vec<String>>,
+        bindings: Option<Vec<Binding>>,
+        blend: Option<RenderBlend>,
+        #[serde(flatten)]
+        schedule: PassSchedule,
     },
 }
 
-#[derive(Debug, Deserialize)]
+impl RenderPass {
+    pub fn schedule(&self) -> &PassSchedule {
+        match self {
+            RenderPass::Compute { schedule, .. }
+            | RenderPass::Render { schedule, .. }
+            | RenderPass::ShaderToy { schedule, .. } => schedule,
+        }
+    }
+
+    // only compute passes can skip frames, a skipped draw would present a blank frame
+    pub fn check_schedule(&self) -> Result<(), String> {
+        let schedule = self.schedule();
+        if schedule.every_n_frames.is_none() && schedule.max_rate_hz.is_none() {
+            return Ok(());
+        }
+
+        if !matches!(self, RenderPass::Compute { .. }) {
+            return Err("every_n_frames and max_rate_hz only apply to compute passes".to_string());
+        }
+        if schedule.every_n_frames == Some(0) {
+            return Err("every_n_frames must be at least 1".to_string());
+        }
+        match schedule.max_rate_hz {
+            Some(rate) if !(rate > 0.0 && rate.is_finite()) => {
+                Err(format!("max_rate_hz must be above 0, found {}", rate))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// controls when a pass runs, by default it runs every frame
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PassSchedule {
+    // name of a bool setting which toggles the pass
+    pub enabled: Option<String>,
+    pub every_n_frames: Option<u32>,
+    pub max_rate_hz: Option<f32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
 pub struct ComputePipeline {
     pub shader: String,
-    pub bindings: Vec<String>,
+    pub bindings: Vec<Binding>,
 }
 
-#[derive(Debug, Deserialize)]
+// a resource id bound at group 0 and its list index,
+// or a table e.g. { resource = "camera", group = 1, binding = 3 }
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Binding {
+    Resource(String),
+    Explicit {
+        resource: String,
+        group: Option<u32>,
+        binding: Option<u32>,
+        usage: Option<TextureUsage>,
+    },
+}
+
+// how a texture is bound, compute passes default to storage and render passes to sampled
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextureUsage {
+    Storage,
+    Sampled,
+}
+
+impl Binding {
+    pub fn resource(&self) -> &String {
+        match self {
+            Binding::Resource(resource) | Binding::Explicit { resource, .. } => resource,
+        }
+    }
+
+    pub fn group(&self) -> u32 {
+        match self {
+            Binding::Resource(_) => 0,
+            Binding::Explicit { group, .. } => group.unwrap_or(0),
+        }
+    }
+
+    // index is the position of the binding in its list
+    pub fn binding(&self, index: usize) -> u32 {
+        match self {
+            Binding::Resource(_) => index as u32,
+            Binding::Explicit { binding, .. } => binding.unwrap_or(index as u32),
+        }
+    }
+
+    pub fn usage(&self, default: TextureUsage) -> TextureUsage {
+        match self {
+            Binding::Resource(_) => default,
+            Binding::Explicit { usage, .. } => usage.unwrap_or(default),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 pub struct RenderPipeline {
     pub shader_vertex: String,
-    pub bindings: Option<Vec<String>>,
+    pub bindings: Option<Vec<Binding>>,
     pub bindings_visibility: Option<Vec<RenderPipelineBindingVisibility>>,
     pub shader_fragment: Option<String>,
     pub topology: RenderPipelineTopology,
@@ -41,9 +144,12 @@ pub struct RenderPipeline {
     pub front_face: RenderPipelineFrontFace,
     pub cull_mode: RenderPipelineCullMode,
     pub vertex: Option<BufferVertex>,
+    pub blend: Option<RenderBlend>,
+    // msaa sample count, 1 (default) or 4
+    pub samples: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum RenderPipelineBindingVisibility {
     None,
     Vertex,
@@ -64,7 +170,7 @@ impl RenderPipelineBindingVisibility {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum RenderPipelineTopology {
     PointList,
     LineList,
@@ -85,7 +191,7 @@ impl RenderPipelineTopology {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum RenderPipelinePolygonMode {
     Fill,
     Line,
@@ -102,7 +208,7 @@ impl RenderPipelinePolygonMode {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum RenderPipelineFrontFace {
     Ccw,
     Cw,
@@ -117,7 +223,7 @@ impl RenderPipelineFrontFace {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum RenderPipelineCullMode {
     Front,
     Back,
@@ -134,12 +240,164 @@ impl RenderPipelineCullMode {
     }
 }
 
+// either a preset e.g. blend = "Additive", or a table with colour and alpha components
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RenderBlend {
+    Preset(RenderBlendPreset),
+    Custom {
+        colour: RenderBlendComponent,
+        alpha: RenderBlendComponent,
+    },
+}
+
+impl RenderBlend {
+    pub fn as_wgpu(&self) -> BlendState {
+        match self {
+            RenderBlend::Preset(preset) => preset.as_wgpu(),
+            RenderBlend::Custom { colour, alpha } => BlendState {
+                color: colour.as_wgpu(),
+                alpha: alpha.as_wgpu(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum RenderBlendPreset {
+    Replace,
+    Alpha,
+    Additive,
+    Premultiplied,
+}
+
+impl RenderBlendPreset {
+    pub fn as_wgpu(&self) -> BlendState {
+        match self {
+            RenderBlendPreset::Replace => BlendState::REPLACE,
+            RenderBlendPreset::Alpha => BlendState::ALPHA_BLENDING,
+            RenderBlendPreset::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            RenderBlendPreset::Premultiplied => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RenderBlendComponent {
+    pub src_factor: RenderBlendFactor,
+    pub dst_factor: RenderBlendFactor,
+    pub operation: Option<RenderBlendOperation>,
+}
+
+impl RenderBlendComponent {
+    pub fn as_wgpu(&self) -> BlendComponent {
+        BlendComponent {
+            src_factor: self.src_factor.as_wgpu(),
+            dst_factor: self.dst_factor.as_wgpu(),
+            operation: self
+                .operation
+                .as_ref()
+                .unwrap_or(&RenderBlendOperation::Add)
+                .as_wgpu(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum RenderBlendFactor {
+    Zero,
+    One,
+    Src,
+    OneMinusSrc,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    Dst,
+    OneMinusDst,
+    DstAlpha,
+    OneMinusDstAlpha,
+    SrcAlphaSaturated,
+}
+
+impl RenderBlendFactor {
+    pub fn as_wgpu(&self) -> BlendFactor {
+        match self {
+            RenderBlendFactor::Zero => BlendFactor::Zero,
+            RenderBlendFactor::One => BlendFactor::One,
+            RenderBlendFactor::Src => BlendFactor::Src,
+            RenderBlendFactor::OneMinusSrc => BlendFactor::OneMinusSrc,
+            RenderBlendFactor::SrcAlpha => BlendFactor::SrcAlpha,
+            RenderBlendFactor::OneMinusSrcAlpha => BlendFactor::OneMinusSrcAlpha,
+            RenderBlendFactor::Dst => BlendFactor::Dst,
+            RenderBlendFactor::OneMinusDst => BlendFactor::OneMinusDst,
+            RenderBlendFactor::DstAlpha => BlendFactor::DstAlpha,
+            RenderBlendFactor::OneMinusDstAlpha => BlendFactor::OneMinusDstAlpha,
+            RenderBlendFactor::SrcAlphaSaturated => BlendFactor::SrcAlphaSaturated,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum RenderBlendOperation {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+impl RenderBlendOperation {
+    pub fn as_wgpu(&self) -> BlendOperation {
+        match self {
+            RenderBlendOperation::Add => BlendOperation::Add,
+            RenderBlendOperation::Subtract => BlendOperation::Subtract,
+            RenderBlendOperation::ReverseSubtract => BlendOperation::ReverseSubtract,
+            RenderBlendOperation::Min => BlendOperation::Min,
+            RenderBlendOperation::Max => BlendOperation::Max,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct RenderClear {
     pub colour: Option<String>,
     pub depth_stencil: Option<()>,
 }
 
+impl RenderClear {
+    // hex colours are srgb, and only converted to linear for srgb targets which encode them again
+    pub fn colour(&self, srgb_target: bool) -> Result<wgpu::Color, ParseHexColorError> {
+        let colour = match self.colour.as_ref() {
+            Some(colour) => HexColor::parse(colour)?,
+            None => return Ok(wgpu::Color::BLACK),
+        };
+
+        let channel = |value: u8| {
+            if srgb_target {
+                srgb_to_linear(value) as f64
+            } else {
+                value as f64 / 255.0
+            }
+        };
+        Ok(wgpu::Color {
+            r: channel(colour.r),
+            g: channel(colour.g),
+            b: channel(colour.b),
+            a: colour.a as f64 / 255.0,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct RenderDraw {
     pub vertex_buffer: Option<String>,
@@ -0,0 +1,37 @@
+/*
+ * Post-processing effects, applied in order to the scene output
+ */
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PostEffect {
+    Bloom {
+        // brightness a pixel needs before it glows
+        threshold: Option<f32>,
+        intensity: Option<f32>,
+        // in pixels
+        radius: Option<f32>,
+    },
+    Vignette {
+        strength: Option<f32>,
+        // distance from the centre where darkening starts, 1.0 is the corner
+        radius: Option<f32>,
+        softness: Option<f32>,
+    },
+    Grain {
+        amount: Option<f32>,
+    },
+    Lut {
+        // id of a lut resource
+        lut: String,
+        strength: Option<f32>,
+    },
+    Blur {
+        // in pixels
+        radius: Option<f32>,
+    },
+    Gamma {
+        power: f32,
+    },
+}
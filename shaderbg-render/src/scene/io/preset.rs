@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::scene::io::setting::{Setting, SettingValue};
+
+// values are written like the defaults in [settings], colours as hex strings
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PresetValue {
+    Bool(bool),
+    Float(f32),
+    Colour3(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub values: HashMap<String, PresetValue>,
+}
+
+impl PresetValue {
+    // None when the value doesn't match the setting's type
+    pub fn to_setting_value(&self, setting: &Setting) -> Option<SettingValue> {
+        match (setting, self) {
+            (Setting::Float { .. }, PresetValue::Float(value)) => Some(SettingValue::Float(*value)),
+            (Setting::Bool { .. }, PresetValue::Bool(value)) => Some(SettingValue::Bool(*value)),
+            (Setting::Colour3 { label, .. }, PresetValue::Colour3(value)) => {
+                SettingValue::from_descriptor(&Setting::Colour3 {
+                    label: label.clone(),
+                    value: value.clone(),
+                })
+                .ok()
+            }
+            _ => None,
+        }
+    }
+}
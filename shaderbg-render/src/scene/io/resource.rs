@@ -1,5 +1,9 @@
+use super::{expression::Expression, pass::ComputePipeline};
 use serde::Deserialize;
-use wgpu::{BufferAddress, VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode};
+use wgpu::{
+    AddressMode, BufferAddress, FilterMode, StorageTextureAccess, TextureFormat, TextureSampleType,
+    VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
+};
 
 /*
  * TODO resource types:
@@ -8,15 +12,20 @@ use wgpu::{BufferAddress, VertexAttribute, VertexBufferLayout, VertexFormat, Ver
  *   fonts?
  */
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Resource {
     Buffer {
         label: Option<String>,
-        size: Option<usize>,
+        size: Option<Expression>,
+        src: Option<String>,
+        format: Option<BufferFormat>,
+        element: Option<BufferElement>,
         storage: Option<BufferStorage>,
         vertex: Option<BufferVertex>,
         vertices: Option<Vec<Vec<f32>>>,
+        init: Option<BufferInit>,
+        reset_on: Option<Vec<String>>,
     },
     Camera {
         projection: CameraProjection,
@@ -40,6 +49,72 @@ pub enum Resource {
         label: Option<String>,
         values: Vec<String>,
     },
+    Texture {
+        label: Option<String>,
+        format: TextureStorageFormat,
+        access: Option<TextureAccess>,
+        // defaults to the output resolution
+        width: Option<Expression>,
+        height: Option<Expression>,
+    },
+    Sampler {
+        label: Option<String>,
+        filter: Option<SamplerFilter>,
+        address_mode: Option<SamplerAddressMode>,
+    },
+    // colour grading table in the .cube format, used by the lut post effect
+    Lut {
+        src: String,
+        label: Option<String>,
+    },
+}
+
+impl Resource {
+    // the kind and path of the file this resource is loaded from, if any
+    pub fn file(&self) -> Option<(&'static str, &String)> {
+        match self {
+            Resource::Shader { src, .. } | Resource::ShaderToy { src, .. } => Some(("Shader", src)),
+            Resource::Buffer { src: Some(src), .. } => Some(("Buffer", src)),
+            Resource::Lut { src, .. } => Some(("Lut", src)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferFormat {
+    Binary,
+    Csv,
+    Json,
+}
+
+impl BufferFormat {
+    pub fn from_src(src: &str) -> Option<BufferFormat> {
+        let extension = src.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "bin" | "raw" => Some(BufferFormat::Binary),
+            "csv" => Some(BufferFormat::Csv),
+            "json" => Some(BufferFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferElement {
+    F32,
+    U32,
+    I32,
+}
+
+// compute pass run when the buffer is created, and again when it is reset
+#[derive(Clone, Debug, Deserialize)]
+pub struct BufferInit {
+    pub label: Option<String>,
+    pub pipeline: ComputePipeline,
+    pub workgroups: [Expression; 3],
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -140,14 +215,96 @@ impl BufferVertexAttributeFormat {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum TextureStorageFormat {
+    Rgba8Unorm,
+    Rgba16Float,
+    Rgba32Float,
+    R32Float,
+    R32Uint,
+}
+
+impl TextureStorageFormat {
+    pub fn as_wgpu(&self) -> TextureFormat {
+        match self {
+            Self::Rgba8Unorm => TextureFormat::Rgba8Unorm,
+            Self::Rgba16Float => TextureFormat::Rgba16Float,
+            Self::Rgba32Float => TextureFormat::Rgba32Float,
+            Self::R32Float => TextureFormat::R32Float,
+            Self::R32Uint => TextureFormat::R32Uint,
+        }
+    }
+
+    // 32 bit float textures can't be filtered without an optional feature
+    pub fn sample_type(&self) -> TextureSampleType {
+        match self {
+            Self::Rgba8Unorm | Self::Rgba16Float => TextureSampleType::Float { filterable: true },
+            Self::Rgba32Float | Self::R32Float => TextureSampleType::Float { filterable: false },
+            Self::R32Uint => TextureSampleType::Uint,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextureAccess {
+    WriteOnly,
+    ReadOnly,
+    ReadWrite,
+}
+
+impl TextureAccess {
+    pub fn as_wgpu(&self) -> StorageTextureAccess {
+        match self {
+            Self::WriteOnly => StorageTextureAccess::WriteOnly,
+            Self::ReadOnly => StorageTextureAccess::ReadOnly,
+            Self::ReadWrite => StorageTextureAccess::ReadWrite,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerFilter {
+    Linear,
+    Nearest,
+}
+
+impl SamplerFilter {
+    pub fn as_wgpu(&self) -> FilterMode {
+        match self {
+            Self::Linear => FilterMode::Linear,
+            Self::Nearest => FilterMode::Nearest,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplerAddressMode {
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+impl SamplerAddressMode {
+    pub fn as_wgpu(&self) -> AddressMode {
+        match self {
+            Self::Clamp => AddressMode::ClampToEdge,
+            Self::Repeat => AddressMode::Repeat,
+            Self::Mirror => AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CameraProjection {
     Perspective,
     Orthographic,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ShaderFormat {
     Wgsl,
@@ -155,7 +312,7 @@ pub enum ShaderFormat {
     Spirv,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ShaderStage {
     Vertex,
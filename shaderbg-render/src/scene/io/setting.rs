@@ -14,6 +14,10 @@ pub enum Setting {
         label: String,
         value: String,
     },
+    Bool {
+        label: String,
+        value: bool,
+    },
 }
 
 #[derive(Debug)]
@@ -21,10 +25,20 @@ pub enum SettingParseError {
     InvalidHex(ParseHexColorError),
 }
 
+pub(crate) fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum SettingValue {
     Float(f32),
     Colour3([f32; 3]),
+    Bool(bool),
 }
 
 impl SettingValue {
@@ -37,12 +51,14 @@ impl SettingValue {
                     Err(error) => return Err(SettingParseError::InvalidHex(error)),
                 };
 
+                // hex colours are srgb, shaders and the colour picker work in linear
                 Ok(SettingValue::Colour3([
-                    (colour.r as f32) / 255.0,
-                    (colour.g as f32) / 255.0,
-                    (colour.b as f32) / 255.0,
+                    srgb_to_linear(colour.r),
+                    srgb_to_linear(colour.g),
+                    srgb_to_linear(colour.b),
                 ]))
             }
+            Setting::Bool { value, .. } => Ok(SettingValue::Bool(*value)),
         }
     }
 
@@ -50,6 +66,7 @@ impl SettingValue {
         match self {
             SettingValue::Float(_) => 4,
             SettingValue::Colour3(_) => 12,
+            SettingValue::Bool(_) => 4,
         }
     }
 
@@ -57,13 +74,29 @@ impl SettingValue {
         match self {
             SettingValue::Float(_) => 4,
             SettingValue::Colour3(_) => 16,
+            SettingValue::Bool(_) => 4,
+        }
+    }
+
+    // numeric value of the setting, used in expressions
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SettingValue::Float(value) => Some(*value as f64),
+            SettingValue::Colour3(_) => None,
+            SettingValue::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
         }
     }
 
     pub fn write(&self, buffer: &mut [u8]) {
+        // bools are not host-shareable in shaders, so they are written as a u32
+        let flag;
         let bytes = match self {
             SettingValue::Float(value) => bytemuck::bytes_of(value),
             SettingValue::Colour3(value) => bytemuck::bytes_of(value),
+            SettingValue::Bool(value) => {
+                flag = *value as u32;
+                bytemuck::bytes_of(&flag)
+            }
         };
         buffer[..self.size()].copy_from_slice(&bytes[..self.size()]);
     }
@@ -1,10 +1,12 @@
 use std::{collections::HashMap, fs, path::PathBuf};
 
-use io::{resource::Resource, setting::SettingParseError, Descriptor};
+use io::{setting::SettingParseError, Descriptor};
 
 pub mod io;
+mod presets;
 mod resources;
 mod settings;
+pub use presets::*;
 pub use resources::*;
 pub use settings::*;
 
@@ -20,12 +22,23 @@ pub enum SceneError {
     ArchiveError(String),
 }
 
+#[derive(Clone)]
 pub struct Scene {
     pub descriptor: Descriptor,
     pub settings: Settings,
+    pub presets: Presets,
     pub files: HashMap<String, Vec<u8>>,
 }
 
+impl std::fmt::Debug for Scene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scene")
+            .field("name", &self.descriptor.meta.name)
+            .field("version", &self.descriptor.meta.version)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Scene {
     pub fn load(
         name: String,
@@ -61,23 +74,20 @@ impl Scene {
         let mut files = HashMap::new();
 
         for (id, resource) in descriptor.resources.iter() {
-            match resource {
-                Resource::Shader { src, .. } | Resource::ShaderToy { src, .. } => {
-                    let path = scene_dir.join(name.clone()).join(src);
-                    let content = match fs::read(path) {
-                        Ok(content) => content,
-                        Err(error) => {
-                            return Err(SceneError::InvalidResource {
-                                kind: "Shader".to_string(),
-                                id: id.clone(),
-                                error: error.to_string(),
-                            })
-                        }
-                    };
-
-                    files.insert(id.clone(), content);
-                }
-                _ => {}
+            if let Some((kind, src)) = resource.file() {
+                let path = scene_dir.join(name.clone()).join(src);
+                let content = match fs::read(path) {
+                    Ok(content) => content,
+                    Err(error) => {
+                        return Err(SceneError::InvalidResource {
+                            kind: kind.to_string(),
+                            id: id.clone(),
+                            error: error.to_string(),
+                        })
+                    }
+                };
+
+                files.insert(id.clone(), content);
             }
         }
 
@@ -90,11 +100,13 @@ impl Scene {
                 Err(error) => return Err(SceneError::SettingsError(error)),
             }
         };
+        let presets = Presets::load(Presets::path(&settings_dir, &name), &descriptor);
 
         Ok(Scene {
             descriptor,
             files,
             settings,
+            presets,
         })
     }
 
@@ -125,19 +137,16 @@ impl Scene {
         let mut files = HashMap::new();
 
         for (id, resource) in descriptor.resources.iter() {
-            match resource {
-                Resource::Shader { src, .. } | Resource::ShaderToy { src, .. } => {
-                    if let Some(file) = virtual_folder.remove(src) {
-                        files.insert(id.clone(), file);
-                    } else {
-                        return Err(SceneError::InvalidResource {
-                            kind: "Shader".to_string(),
-                            id: id.clone(),
-                            error: "file not provided".to_string(),
-                        });
-                    }
+            if let Some((kind, src)) = resource.file() {
+                if let Some(file) = virtual_folder.remove(src) {
+                    files.insert(id.clone(), file);
+                } else {
+                    return Err(SceneError::InvalidResource {
+                        kind: kind.to_string(),
+                        id: id.clone(),
+                        error: "file not provided".to_string(),
+                    });
                 }
-                _ => {}
             }
         }
 
@@ -145,11 +154,13 @@ impl Scene {
             Ok(settings) => settings,
             Err(error) => return Err(SceneError::SettingsError(error)),
         };
+        let presets = Presets::new(&descriptor);
 
         Ok(Scene {
             descriptor,
             files,
             settings,
+            presets,
         })
     }
 }
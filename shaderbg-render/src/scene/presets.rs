@@ -0,0 +1,212 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::scene::{
+    io::{setting::SettingValue, Descriptor},
+    Settings,
+};
+
+#[derive(Clone, Debug)]
+pub struct PresetEntry {
+    pub name: String,
+    pub values: HashMap<String, SettingValue>,
+    // declared in scene.toml, so it can't be renamed or deleted
+    pub builtin: bool,
+}
+
+// the scene's presets, followed by the ones saved by the user
+#[derive(Clone, Debug, Default)]
+pub struct Presets {
+    entries: Vec<PresetEntry>,
+}
+
+impl Presets {
+    pub fn new(descriptor: &Descriptor) -> Presets {
+        let mut entries: Vec<PresetEntry> = Vec::new();
+
+        for preset in descriptor.presets.iter() {
+            let mut values = HashMap::new();
+            for (key, value) in preset.values.iter() {
+                let setting = match descriptor.settings.get(key) {
+                    Some(setting) => setting,
+                    None => {
+                        eprintln!("Preset {}: unknown setting {}", preset.name, key);
+                        continue;
+                    }
+                };
+                match value.to_setting_value(setting) {
+                    Some(value) => {
+                        values.insert(key.clone(), value);
+                    }
+                    None => eprintln!(
+                        "Preset {}: {:?} does not match setting {}",
+                        preset.name, value, key
+                    ),
+                }
+            }
+
+            if entries.iter().any(|entry| entry.name == preset.name) {
+                eprintln!("Preset {} is declared twice, ignoring", preset.name);
+                continue;
+            }
+            entries.push(PresetEntry {
+                name: preset.name.clone(),
+                values,
+                builtin: true,
+            });
+        }
+
+        Presets { entries }
+    }
+
+    // kept apart from the settings files, so no scene name can collide with them
+    pub fn path(settings_dir: &Path, scene: &str) -> PathBuf {
+        settings_dir.join("presets").join(format!("{}.toml", scene))
+    }
+
+    // user presets are stored like settings files, one table per preset
+    pub fn load(path: PathBuf, descriptor: &Descriptor) -> Presets {
+        let mut presets = Presets::new(descriptor);
+
+        let presets_string = match fs::read_to_string(path.clone()) {
+            Ok(presets) => presets,
+            Err(_) => return presets,
+        };
+
+        let saved: BTreeMap<String, HashMap<String, SettingValue>> =
+            match toml::from_str(&presets_string) {
+                Ok(saved) => saved,
+                Err(error) => {
+                    eprintln!(
+                        "Failed to parse presets file {:?}:\n{:#?}\nIgnoring",
+                        path, error
+                    );
+                    return presets;
+                }
+            };
+
+        for (name, mut values) in saved {
+            if presets.get(&name).is_some() {
+                eprintln!(
+                    "Preset {} is part of the scene, ignoring the saved copy",
+                    name
+                );
+                continue;
+            }
+            values.retain(|key, value| match descriptor.settings.get(key) {
+                Some(setting) => SettingValue::from_descriptor(setting)
+                    .map(|setting| {
+                        std::mem::discriminant(value) == std::mem::discriminant(&setting)
+                    })
+                    .unwrap_or(false),
+                None => false,
+            });
+            presets.entries.push(PresetEntry {
+                name,
+                values,
+                builtin: false,
+            });
+        }
+
+        presets
+    }
+
+    pub fn save(&self, path: PathBuf) -> Result<(), String> {
+        let saved: BTreeMap<&String, &HashMap<String, SettingValue>> = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.builtin)
+            .map(|entry| (&entry.name, &entry.values))
+            .collect();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let presets_string = toml::to_string(&saved).map_err(|e| e.to_string())?;
+        fs::write(path, presets_string).map_err(|e| e.to_string())
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PresetEntry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PresetEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    // the setting changes that apply a preset, settings it doesn't mention are left alone
+    pub fn changes(&self, name: &str) -> Option<Vec<(String, SettingValue)>> {
+        self.get(name).map(|entry| {
+            entry
+                .values
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+    }
+
+    // saves every setting, replacing a user preset with the same name
+    pub fn save_preset(&mut self, name: &str, settings: &Settings) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Presets need a name".to_string());
+        }
+
+        let values = settings
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        match self.entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) if entry.builtin => Err(format!("{} is part of the scene", name)),
+            Some(entry) => {
+                entry.values = values;
+                Ok(())
+            }
+            None => {
+                self.entries.push(PresetEntry {
+                    name: name.to_string(),
+                    values,
+                    builtin: false,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("Presets need a name".to_string());
+        }
+        if self.get(new_name).is_some() {
+            return Err(format!("{} already exists", new_name));
+        }
+
+        let entry = self.user_preset(name)?;
+        entry.name = new_name.to_string();
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        self.user_preset(name)?;
+        self.entries.retain(|entry| entry.name != name);
+        Ok(())
+    }
+
+    fn user_preset(&mut self, name: &str) -> Result<&mut PresetEntry, String> {
+        match self.entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) if entry.builtin => Err(format!("{} is part of the scene", name)),
+            Some(entry) => Ok(entry),
+            None => Err(format!("No preset named {}", name)),
+        }
+    }
+}
@@ -4,29 +4,47 @@
  */
 
 use cgmath::Point3;
-use std::{borrow::Cow, collections::HashMap, mem, ops::Deref, str::Utf8Error};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    mem,
+    ops::Deref,
+    str::Utf8Error,
+};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingType, BlendState, Buffer, BufferAddress, BufferBindingType,
-    BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, CommandEncoder, ComputePipeline,
-    ComputePipelineDescriptor, Device, FragmentState, PipelineLayoutDescriptor, PrimitiveState,
-    Queue, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor,
-    TextureFormat, TextureView, VertexAttribute, VertexBufferLayout, VertexState,
+    BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferAddress,
+    BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites,
+    CommandEncoder, ComputePipeline, ComputePipelineDescriptor, Device, FilterMode, FragmentState,
+    PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPipeline, RenderPipelineDescriptor,
+    Sampler, SamplerBindingType, SamplerDescriptor, ShaderModule, ShaderModuleDescriptor,
+    ShaderStages, Texture, TextureFormat, TextureView, TextureViewDimension, VertexAttribute,
+    VertexBufferLayout, VertexState,
 };
 
 use crate::{
     gfx::{
-        buffer::{CameraMatrix, ShaderToy, Time},
+        buffer::{CameraMatrix, Output, ShaderToy, Time},
         camera::Camera,
+        post_process::PostProcess,
+        profiler::{GpuTimer, PassTime, ResourceMemory},
         vertices::VERTICES_QUAD,
     },
     scene::{
         io::{
-            pass::{RenderClear, RenderDraw, RenderPass, RenderPipelineBindingVisibility},
+            buffer::BufferContents,
+            expression::Expression,
+            pass::{
+                Binding, ComputePipeline as PassComputePipeline, PassSchedule, RenderDraw,
+                RenderPass, TextureUsage,
+            },
+            post_process::PostEffect,
             resource::{
-                BufferStorage, BufferStorageType, BufferVertex, BufferVertexAttribute,
-                BufferVertexAttributeFormat, BufferVertexStep, Resource, ShaderFormat,
+                BufferElement, BufferFormat, BufferStorage, BufferStorageType, BufferVertex,
+                BufferVertexAttribute, BufferVertexAttributeFormat, BufferVertexStep, Resource,
+                SamplerAddressMode, SamplerFilter, ShaderFormat, TextureAccess,
+                TextureStorageFormat,
             },
             setting::SettingValue,
         },
@@ -34,12 +52,24 @@ use crate::{
     },
 };
 
+// lowest render scale, below this scenes become unrecognisable
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+
 #[allow(dead_code)]
 struct BufferResource {
     buffer: Buffer,
     vertex: Option<BufferVertex>,
     vertex_count: Option<u32>,
     storage: Option<BufferStorage>,
+    size: Option<BufferSize>,
+}
+
+// buffers sized by an expression are recreated when the expression changes
+struct BufferSize {
+    label: Option<String>,
+    usage: BufferUsages,
+    expression: Expression,
+    bytes: u64,
 }
 
 struct ShaderResource {
@@ -53,24 +83,121 @@ enum PassResource {
     Compute {
         label: Option<String>,
         pipeline: ComputePipeline,
-        bind_group: BindGroup,
-        workgroups: [u32; 3],
+        bind_groups: Vec<usize>,
+        workgroups: [Expression; 3],
+        dispatch: [u32; 3],
     },
     Render {
         label: Option<String>,
         pipeline: RenderPipeline,
-        bind_group: BindGroup,
-        #[allow(dead_code)]
-        clear: Option<RenderClear>,
+        bind_groups: Vec<usize>,
+        // only used when the pass is the first to draw into its target that frame
+        clear: Option<wgpu::Color>,
         draw: Vec<RenderDraw>,
+        samples: u32,
     },
     ShaderToy {
         label: Option<String>,
         pipeline: RenderPipeline,
-        bind_group: BindGroup,
+        bind_groups: Vec<usize>,
     },
 }
 
+impl PassResource {
+    fn label(&self) -> Option<&str> {
+        match self {
+            PassResource::Compute { label, .. }
+            | PassResource::Render { label, .. }
+            | PassResource::ShaderToy { label, .. } => label.as_deref(),
+        }
+    }
+}
+
+// tracks when a pass last ran, one per entry in passes
+struct PassTiming {
+    schedule: PassSchedule,
+    frames_waited: u32,
+    last_run: Option<u32>,
+}
+
+impl PassTiming {
+    fn enabled(&self, variables: &HashMap<String, f64>) -> bool {
+        match self.schedule.enabled.as_ref() {
+            Some(setting) => variables.get(setting).copied().unwrap_or(0.0) != 0.0,
+            None => true,
+        }
+    }
+
+    // now is the scene time in milliseconds
+    fn ready(&mut self, variables: &HashMap<String, f64>, now: u32) -> bool {
+        if !self.enabled(variables) {
+            return false;
+        }
+
+        self.frames_waited = self.frames_waited.saturating_add(1);
+
+        if let Some(last_run) = self.last_run {
+            if let Some(frames) = self.schedule.every_n_frames {
+                if self.frames_waited < frames {
+                    return false;
+                }
+            }
+
+            // the clock jumps backwards when the scene restarts, so run straight away
+            if let Some(rate) = self.schedule.max_rate_hz {
+                if now >= last_run && ((now - last_run) as f32) < 1000.0 / rate {
+                    return false;
+                }
+            }
+        }
+
+        self.frames_waited = 0;
+        self.last_run = Some(now);
+        true
+    }
+}
+
+// seeds a buffer, pending when the buffer needs to be (re)initialised
+struct InitPass {
+    buffer: String,
+    reset_on: Vec<String>,
+    pass: PassResource,
+    pending: bool,
+}
+
+// the layout and entries are kept so the bind group can be rebuilt
+// when a buffer it references is recreated
+struct SharedBindGroup {
+    layout: BindGroupLayout,
+    entries: Vec<BindGroupEntryResource>,
+    bind_group: BindGroup,
+}
+
+#[derive(Debug)]
+struct BindGroupEntryResource {
+    binding: u32,
+    resource: String,
+    visibility: ShaderStages,
+    usage: TextureUsage,
+}
+
+// storage textures are recreated when their size changes
+struct TextureResource {
+    label: Option<String>,
+    format: TextureStorageFormat,
+    access: TextureAccess,
+    width: Expression,
+    height: Expression,
+    size: (u32, u32),
+    texture: Texture,
+    view: TextureView,
+}
+
+struct SamplerResource {
+    sampler: Sampler,
+    filter: SamplerFilter,
+}
+
 #[allow(dead_code)]
 struct CameraResource {
     camera: Camera,
@@ -106,6 +233,18 @@ pub enum ResourceError {
     MissingSetting {
         id: String,
     },
+    Validation(String),
+}
+
+// options chosen by the application rather than the scene
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    // caps the msaa sample count requested by render passes
+    pub max_samples: Option<u32>,
+    // replaces the scene's post-processing effects, an empty list disables them
+    pub post_process: Option<Vec<PostEffect>>,
+    // fraction of the output resolution the scene is rendered at, then upscaled
+    pub render_scale: Option<f32>,
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -116,12 +255,36 @@ enum ShaderEntrypointType {
 }
 
 pub struct Resources {
+    format: TextureFormat,
+    // the size the scene is rendered at, the output size scaled by render_scale
+    width: u32,
+    height: u32,
+    output_width: u32,
+    output_height: u32,
+    render_scale: f32,
+    options: RenderOptions,
+    // multisampled colour targets, keyed by sample count
+    multisample_targets: HashMap<u32, TextureView>,
     buffers: HashMap<String, BufferResource>,
     cameras: HashMap<String, CameraResource>,
     uniforms: HashMap<String, UniformResource>,
+    textures: HashMap<String, TextureResource>,
+    samplers: HashMap<String, SamplerResource>,
     passes: Vec<PassResource>,
+    schedules: Vec<PassTiming>,
+    bind_groups: Vec<SharedBindGroup>,
+    bind_group_lookup: HashMap<String, usize>,
     setting_lookup: HashMap<String, String>,
     updated_uniforms: Vec<String>,
+    variables: HashMap<String, f64>,
+    init_passes: Vec<InitPass>,
+    warmup: u32,
+    warmup_remaining: u32,
+    post_process: Option<PostProcess>,
+    timer: Option<GpuTimer>,
+    frozen: bool,
+    // the frozen frame has been drawn, into the post-processing input
+    frozen_frame: bool,
 }
 
 impl Resources {
@@ -132,19 +295,36 @@ impl Resources {
         width: u32,
         height: u32,
         format: TextureFormat,
+        options: &RenderOptions,
     ) -> Result<Resources, ResourceError> {
         let descriptor = &scene.descriptor;
 
+        let render_scale = options
+            .render_scale
+            .map_or(1.0, |scale| scale.clamp(MIN_RENDER_SCALE, 1.0));
+        let (output_width, output_height) = (width, height);
+        let (width, height) = Resources::scaled_size(width, height, render_scale);
+
         let passes = Vec::<PassResource>::new();
 
         let mut buffers = HashMap::new();
         let mut cameras = HashMap::new();
         let mut uniforms = HashMap::new();
+        let mut textures = HashMap::new();
+        let mut samplers = HashMap::new();
 
         let mut shaders: HashMap<String, ShaderResource> = HashMap::new();
 
         let mut setting_lookup: HashMap<String, String> = HashMap::new();
 
+        let variables = Resources::build_variables(scene, width, height);
+
+        let warmup = descriptor
+            .simulation
+            .as_ref()
+            .and_then(|simulation| simulation.warmup)
+            .unwrap_or(0);
+
         // Construct builtin uniforms
         buffers.insert(
             "time".to_string(),
@@ -158,6 +338,7 @@ impl Resources {
                 vertex: None,
                 vertex_count: None,
                 storage: None,
+                size: None,
             },
         );
         uniforms.insert("time".to_string(), UniformResource::Internal);
@@ -174,19 +355,41 @@ impl Resources {
                 vertex: None,
                 vertex_count: None,
                 storage: None,
+                size: None,
             },
         );
         uniforms.insert("shadertoy".to_string(), UniformResource::Internal);
 
+        buffers.insert(
+            "output".to_string(),
+            BufferResource {
+                buffer: device.create_buffer_init(&BufferInitDescriptor {
+                    label: Some("Output Uniform"),
+                    contents: bytemuck::cast_slice(&[Output::new(format)]),
+                    usage: BufferUsages::UNIFORM,
+                }),
+                vertex: None,
+                vertex_count: None,
+                storage: None,
+                size: None,
+            },
+        );
+        uniforms.insert("output".to_string(), UniformResource::Internal);
+
         for (id, res) in descriptor.resources.iter() {
             match res {
                 Resource::Buffer {
                     label,
                     size,
+                    src,
+                    format,
+                    element,
                     storage,
                     vertex,
                     vertices,
+                    ..
                 } => {
+                    let size_expression = size;
                     let mut vertex = vertex.clone();
                     let mut usage = BufferUsages::empty();
                     if storage.is_some() {
@@ -196,8 +399,93 @@ impl Resources {
                         usage |= BufferUsages::VERTEX;
                     }
 
-                    let size = if let Some(size) = size {
-                        *size
+                    if src.is_some() && (size.is_some() || vertices.is_some()) {
+                        return Err(ResourceError::InvalidResource {
+                            id: id.clone(),
+                            reason: "Buffer with src cannot also have size or vertices".to_string(),
+                        });
+                    }
+
+                    let file_contents = if let Some(src) = src {
+                        let format = match format.clone().or_else(|| BufferFormat::from_src(src)) {
+                            Some(format) => format,
+                            None => {
+                                return Err(ResourceError::InvalidResource {
+                                    id: id.clone(),
+                                    reason: format!("Cannot infer format of {}", src),
+                                })
+                            }
+                        };
+                        let content = match scene.files.get(id) {
+                            Some(content) => content,
+                            None => {
+                                return Err(ResourceError::InvalidResource {
+                                    id: id.clone(),
+                                    reason: format!("File {} not loaded", src),
+                                })
+                            }
+                        };
+                        match BufferContents::parse(
+                            content,
+                            &format,
+                            element.unwrap_or(BufferElement::F32),
+                        ) {
+                            Ok(contents) => Some(contents),
+                            Err(reason) => {
+                                return Err(ResourceError::InvalidResource {
+                                    id: id.clone(),
+                                    reason: format!("{}: {}", src, reason),
+                                })
+                            }
+                        }
+                    } else {
+                        None
+                    };
+
+                    let size = if let Some(contents) = file_contents.as_ref() {
+                        // rows of 2 or 3 floats can be used as vertices without a layout
+                        if vertex.is_none() && storage.is_none() {
+                            let attribute_format = match (element, contents.row_length) {
+                                (None | Some(BufferElement::F32), Some(2)) => {
+                                    BufferVertexAttributeFormat::Float32x2
+                                }
+                                (None | Some(BufferElement::F32), Some(3)) => {
+                                    BufferVertexAttributeFormat::Float32x3
+                                }
+                                _ => {
+                                    return Err(ResourceError::InvalidResource {
+                                        id: id.clone(),
+                                        reason: "Buffer needs a vertex layout or storage"
+                                            .to_string(),
+                                    })
+                                }
+                            };
+                            let length = contents.row_length.unwrap_or(0);
+                            vertex = Some(BufferVertex {
+                                stride: length * mem::size_of::<f32>(),
+                                step: Some(BufferVertexStep::Vertex),
+                                attributes: vec![BufferVertexAttribute {
+                                    offset: 0,
+                                    location: 0,
+                                    format: attribute_format,
+                                }],
+                            });
+                        }
+                        if vertex.is_some() {
+                            usage |= BufferUsages::VERTEX;
+                        }
+
+                        contents.bytes.len()
+                    } else if let Some(size) = size {
+                        match Resources::evaluate_buffer_size(size, &variables) {
+                            Ok(size) => size as usize,
+                            Err(reason) => {
+                                return Err(ResourceError::InvalidResource {
+                                    id: id.clone(),
+                                    reason,
+                                })
+                            }
+                        }
                     } else if let Some(vertices) = vertices {
                         if vertices.is_empty() {
                             return Err(ResourceError::InvalidResource {
@@ -234,7 +522,13 @@ impl Resources {
                         });
                     };
 
-                    let buffer = if let Some(vertices) = vertices {
+                    let buffer = if let Some(contents) = file_contents {
+                        device.create_buffer_init(&BufferInitDescriptor {
+                            label: label.clone().as_deref(),
+                            contents: contents.bytes.as_slice(),
+                            usage,
+                        })
+                    } else if let Some(vertices) = vertices {
                         let mut contents = Vec::<u8>::new();
 
                         let length = vertices.len();
@@ -272,6 +566,12 @@ impl Resources {
                             vertex: vertex.clone(),
                             vertex_count: vertex.as_ref().map(|v| (size / v.stride) as u32),
                             storage: storage.clone(),
+                            size: size_expression.as_ref().map(|expression| BufferSize {
+                                label: label.clone(),
+                                usage,
+                                expression: expression.clone(),
+                                bytes: size as u64,
+                            }),
                         },
                     );
                 }
@@ -306,6 +606,7 @@ impl Resources {
                             vertex: None,
                             vertex_count: None,
                             storage: None,
+                            size: None,
                         },
                     );
                 }
@@ -416,6 +717,7 @@ impl Resources {
                             vertex: None,
                             vertex_count: None,
                             storage: None,
+                            size: None,
                         },
                     );
 
@@ -427,6 +729,70 @@ impl Resources {
                         },
                     );
                 }
+                Resource::Texture {
+                    label,
+                    format,
+                    access,
+                    width,
+                    height,
+                } => {
+                    let width = width
+                        .clone()
+                        .unwrap_or(Expression::Source("width".to_string()));
+                    let height = height
+                        .clone()
+                        .unwrap_or(Expression::Source("height".to_string()));
+
+                    let size = match Resources::evaluate_texture_size(&width, &height, &variables) {
+                        Ok(size) => size,
+                        Err(reason) => {
+                            return Err(ResourceError::InvalidResource {
+                                id: id.clone(),
+                                reason,
+                            })
+                        }
+                    };
+
+                    let (texture, view) =
+                        Resources::create_storage_texture(device, label, *format, size);
+
+                    textures.insert(
+                        id.clone(),
+                        TextureResource {
+                            label: label.clone(),
+                            format: *format,
+                            access: access.unwrap_or(TextureAccess::WriteOnly),
+                            width,
+                            height,
+                            size,
+                            texture,
+                            view,
+                        },
+                    );
+                }
+                Resource::Sampler {
+                    label,
+                    filter,
+                    address_mode,
+                } => {
+                    let filter = filter.unwrap_or(SamplerFilter::Linear);
+                    let address_mode = address_mode.unwrap_or(SamplerAddressMode::Clamp).as_wgpu();
+
+                    let sampler = device.create_sampler(&SamplerDescriptor {
+                        label: label.as_deref(),
+                        address_mode_u: address_mode,
+                        address_mode_v: address_mode,
+                        address_mode_w: address_mode,
+                        mag_filter: filter.as_wgpu(),
+                        min_filter: filter.as_wgpu(),
+                        mipmap_filter: FilterMode::Nearest,
+                        ..Default::default()
+                    });
+
+                    samplers.insert(id.clone(), SamplerResource { sampler, filter });
+                }
+                // loaded by the post-process stage
+                Resource::Lut { .. } => {}
                 Resource::ShaderToy { label, .. } => {
                     let shader_source = scene
                         .files
@@ -443,12 +809,17 @@ impl Resources {
                     let full_source_string =
                         shader_harness.replace("{{SOURCE}}", shader_source_string);
 
+                    let mut defines = naga::FastHashMap::default();
+                    if format.is_srgb() {
+                        defines.insert("OUTPUT_SRGB".to_string(), "1".to_string());
+                    }
+
                     let module = device.create_shader_module(ShaderModuleDescriptor {
                         label: label.as_deref(),
                         source: wgpu::ShaderSource::Glsl {
                             shader: Cow::Owned(full_source_string),
                             stage: naga::ShaderStage::Fragment,
-                            defines: Default::default(),
+                            defines,
                         },
                     });
                     shaders.insert(
@@ -483,20 +854,116 @@ impl Resources {
             }
         }
 
+        let post_process =
+            Resources::build_post_process(scene, device, width, height, format, options)?;
+
         let mut resources = Resources {
+            format,
+            width,
+            height,
+            output_width,
+            output_height,
+            render_scale,
+            options: options.clone(),
+            multisample_targets: HashMap::new(),
             buffers,
             cameras,
             uniforms,
+            textures,
+            samplers,
             passes,
             setting_lookup,
             updated_uniforms: Vec::new(),
+            variables,
+            schedules: Vec::new(),
+            bind_groups: Vec::new(),
+            bind_group_lookup: HashMap::new(),
+            init_passes: Vec::new(),
+            warmup,
+            warmup_remaining: warmup,
+            post_process,
+            timer: None,
+            frozen: false,
+            frozen_frame: false,
         };
 
-        for pass in descriptor.render_passes.iter() {
-            let pass_resource = match pass {
-                RenderPass::Compute { .. } => {
-                    resources.build_compute_pipeline(pass, device, &shaders)?
+        for (id, res) in descriptor.resources.iter() {
+            // typos in reset_on would otherwise never reset anything
+            if let Resource::Buffer {
+                init,
+                reset_on: Some(reset_on),
+                ..
+            } = res
+            {
+                if init.is_none() {
+                    return Err(ResourceError::InvalidResource {
+                        id: id.clone(),
+                        reason: "reset_on needs an init pass".to_string(),
+                    });
+                }
+                if let Some(key) = reset_on
+                    .iter()
+                    .find(|key| scene.settings.get(key).is_none())
+                {
+                    return Err(ResourceError::InvalidResource {
+                        id: id.clone(),
+                        reason: format!("reset_on names unknown setting {}", key),
+                    });
                 }
+            }
+
+            if let Resource::Buffer {
+                init: Some(init),
+                reset_on,
+                ..
+            } = res
+            {
+                let pass = resources.build_compute_pipeline(
+                    &init.label,
+                    &init.pipeline,
+                    &init.workgroups,
+                    device,
+                    &shaders,
+                )?;
+                resources.init_passes.push(InitPass {
+                    buffer: id.clone(),
+                    reset_on: reset_on.clone().unwrap_or_default(),
+                    pass,
+                    pending: true,
+                });
+            }
+        }
+
+        // a multisampled pass resolves over the whole output, erasing passes drawn before it
+        let mut sample_counts = descriptor
+            .render_passes
+            .iter()
+            .filter_map(|pass| match pass {
+                RenderPass::Compute { .. } => None,
+                RenderPass::Render { pipeline, .. } => Some(pipeline.samples.unwrap_or(1)),
+                RenderPass::ShaderToy { .. } => Some(1),
+            });
+        if let Some(first) = sample_counts.next() {
+            if let Some(other) = sample_counts.find(|samples| *samples != first) {
+                return Err(ResourceError::InvalidResource {
+                    id: "render_passes".to_string(),
+                    reason: format!(
+                        "Passes drawing into the output must use the same sample count, found {} and {}",
+                        first, other
+                    ),
+                });
+            }
+        }
+
+        for (index, pass) in descriptor.render_passes.iter().enumerate() {
+            let pass_resource = match pass {
+                RenderPass::Compute {
+                    label,
+                    pipeline,
+                    workgroups,
+                    ..
+                } => resources
+                    .build_compute_pipeline(label, pipeline, workgroups, device, &shaders)?,
                 RenderPass::Render { .. } => {
                     resources.build_render_pipeline(pass, device, format, &shaders)?
                 }
@@ -504,12 +971,87 @@ impl Resources {
                     resources.build_shadertoy_pipeline(pass, device, format, &shaders)?
                 }
             };
+            if let Err(reason) = pass.check_schedule() {
+                return Err(ResourceError::InvalidResource {
+                    id: pass_resource
+                        .label()
+                        .map_or_else(|| format!("Pass {}", index), str::to_string),
+                    reason,
+                });
+            }
             resources.passes.push(pass_resource);
+
+            let schedule = pass.schedule();
+            if let Some(enabled) = schedule.enabled.as_ref() {
+                match scene.settings.get(enabled) {
+                    Some(SettingValue::Bool(_)) => {}
+                    Some(setting) => {
+                        return Err(ResourceError::IncorrectResource {
+                            id: enabled.clone(),
+                            expected: "Bool setting".to_string(),
+                            actual: format!("{:?}", setting),
+                        })
+                    }
+                    None => {
+                        return Err(ResourceError::MissingSetting {
+                            id: enabled.clone(),
+                        })
+                    }
+                }
+            }
+            resources.schedules.push(PassTiming {
+                schedule: schedule.clone(),
+                frames_waited: 0,
+                last_run: None,
+            });
         }
 
         Ok(resources)
     }
 
+    fn build_post_process(
+        scene: &Scene,
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        options: &RenderOptions,
+    ) -> Result<Option<PostProcess>, ResourceError> {
+        let effects = match options.post_process.as_ref() {
+            Some(effects) => effects.as_slice(),
+            None => scene.descriptor.post_process.as_deref().unwrap_or(&[]),
+        };
+
+        // scaled rendering needs the stage to upscale into the view
+        if effects.is_empty() && options.render_scale.is_none() {
+            return Ok(None);
+        }
+
+        for effect in effects.iter() {
+            if let PostEffect::Lut { lut, .. } = effect {
+                match scene.descriptor.resources.get(lut) {
+                    Some(Resource::Lut { .. }) => {}
+                    Some(resource) => {
+                        return Err(ResourceError::IncorrectResource {
+                            id: lut.clone(),
+                            expected: "Lut".to_string(),
+                            actual: format!("{:?}", resource),
+                        })
+                    }
+                    None => return Err(ResourceError::MissingResource { id: lut.clone() }),
+                }
+            }
+        }
+
+        match PostProcess::new(device, effects, &scene.files, format, format, width, height) {
+            Ok(post_process) => Ok(Some(post_process)),
+            Err(reason) => Err(ResourceError::InvalidResource {
+                id: "post_process".to_string(),
+                reason,
+            }),
+        }
+    }
+
     fn get_shader_and_entrypoint<'a>(
         id: &String,
         entrypoint_type: ShaderEntrypointType,
@@ -566,36 +1108,72 @@ impl Resources {
         Ok((&shader.module, entrypoint.clone()))
     }
 
-    fn build_bind_group(
-        &self,
+    // builds one bind group per group index used by the bindings, reusing
+    // identical groups from earlier passes. returns indices into bind_groups
+    fn build_bind_groups(
+        &mut self,
         label: &Option<String>,
-        bindings: Option<&Vec<String>>,
-        bindings_visibility: Option<&Vec<RenderPipelineBindingVisibility>>,
+        bindings: &[Binding],
+        bindings_visibility: &[ShaderStages],
+        texture_usage: TextureUsage,
         device: &Device,
-    ) -> Result<(BindGroupLayout, BindGroup), ResourceError> {
-        let mut bind_group_layout_entries = Vec::<BindGroupLayoutEntry>::new();
+    ) -> Result<Vec<usize>, ResourceError> {
+        let mut groups: BTreeMap<u32, Vec<BindGroupEntryResource>> = BTreeMap::new();
+
+        for (idx, binding) in bindings.iter().enumerate() {
+            let entries = groups.entry(binding.group()).or_default();
+            let entry = BindGroupEntryResource {
+                binding: binding.binding(idx),
+                resource: binding.resource().clone(),
+                visibility: bindings_visibility[idx],
+                usage: binding.usage(texture_usage),
+            };
 
-        if let Some(bindings) = bindings {
-            let compute_vis = bindings
-                .iter()
-                .map(|_| RenderPipelineBindingVisibility::Compute)
-                .collect();
+            if entries.iter().any(|other| other.binding == entry.binding) {
+                return Err(ResourceError::InvalidResource {
+                    id: entry.resource,
+                    reason: format!(
+                        "Binding {} in group {} is used more than once",
+                        entry.binding,
+                        binding.group()
+                    ),
+                });
+            }
+            entries.push(entry);
+        }
 
-            let visibilities = if let Some(vis) = bindings_visibility {
-                vis
-            } else {
-                &compute_vis
-            };
+        let group_count = groups.keys().last().map_or(0, |group| group + 1);
+        let mut bind_groups = Vec::new();
+
+        // pipeline layouts can't have gaps, so unused groups get an empty bind group
+        for group in 0..group_count {
+            let mut entries = groups.remove(&group).unwrap_or_default();
+            entries.sort_by_key(|entry| entry.binding);
+
+            let key = format!("{:?}", entries);
+            if let Some(index) = self.bind_group_lookup.get(&key) {
+                bind_groups.push(*index);
+                continue;
+            }
+
+            let mut bind_group_layout_entries = Vec::<BindGroupLayoutEntry>::new();
+
+            for entry in entries.iter() {
+                let binding = &entry.resource;
+                let buffer_binding = |ty| BindingType::Buffer {
+                    ty,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                };
 
-            for (idx, binding) in bindings.iter().enumerate() {
                 let bind_type =
                     if self.cameras.contains_key(binding) || self.uniforms.contains_key(binding) {
-                        BufferBindingType::Uniform
+                        buffer_binding(BufferBindingType::Uniform)
                     } else if let Some(buf) = self.buffers.get(binding) {
                         if let Some(storage) = buf.storage.as_ref() {
-                            BufferBindingType::Storage {
+                            buffer_binding(BufferBindingType::Storage {
                                 read_only: storage.storage_type == BufferStorageType::Read,
-                            }
+                            })
                         } else {
                             return Err(ResourceError::InvalidResource {
                                 id: binding.clone(),
@@ -603,6 +1181,24 @@ impl Resources {
                                     .to_string(),
                             });
                         }
+                    } else if let Some(texture) = self.textures.get(binding) {
+                        match entry.usage {
+                            TextureUsage::Storage => BindingType::StorageTexture {
+                                access: texture.access.as_wgpu(),
+                                format: texture.format.as_wgpu(),
+                                view_dimension: TextureViewDimension::D2,
+                            },
+                            TextureUsage::Sampled => BindingType::Texture {
+                                sample_type: texture.format.sample_type(),
+                                view_dimension: TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                        }
+                    } else if let Some(sampler) = self.samplers.get(binding) {
+                        BindingType::Sampler(match sampler.filter {
+                            SamplerFilter::Linear => SamplerBindingType::Filtering,
+                            SamplerFilter::Nearest => SamplerBindingType::NonFiltering,
+                        })
                     } else {
                         return Err(ResourceError::IncorrectResource {
                             id: binding.clone(),
@@ -612,75 +1208,96 @@ impl Resources {
                     };
 
                 bind_group_layout_entries.push(BindGroupLayoutEntry {
-                    binding: idx as u32,
-                    visibility: visibilities[idx].as_wgpu(),
-                    ty: BindingType::Buffer {
-                        ty: bind_type,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
+                    binding: entry.binding,
+                    visibility: entry.visibility,
+                    ty: bind_type,
                     count: None,
                 });
             }
+
+            let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: label
+                    .clone()
+                    .map(|s| format!("{} (Bind Group Layout {})", s, group))
+                    .as_deref(),
+                entries: bind_group_layout_entries.as_slice(),
+            });
+
+            let bind_group = self.create_bind_group(&bind_group_layout, &entries, device);
+
+            self.bind_group_lookup.insert(key, self.bind_groups.len());
+            bind_groups.push(self.bind_groups.len());
+            self.bind_groups.push(SharedBindGroup {
+                layout: bind_group_layout,
+                entries,
+                bind_group,
+            });
         }
 
-        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            label: label
-                .clone()
-                .map(|s| format!("{} (Bind Group Layout)", s))
-                .as_deref(),
-            entries: bind_group_layout_entries.as_slice(),
-        });
+        Ok(bind_groups)
+    }
+
+    fn bind_group_layouts(&self, bind_groups: &[usize]) -> Vec<&BindGroupLayout> {
+        bind_groups
+            .iter()
+            .map(|index| &self.bind_groups[*index].layout)
+            .collect()
+    }
 
+    fn create_bind_group(
+        &self,
+        layout: &BindGroupLayout,
+        entries: &[BindGroupEntryResource],
+        device: &Device,
+    ) -> BindGroup {
         let mut bind_group_entries = Vec::<BindGroupEntry>::new();
 
-        if let Some(bindings) = bindings {
-            for (idx, binding) in bindings.iter().enumerate() {
-                let buffer = if let Some(buffer) = self.buffers.get(binding) {
-                    buffer
-                } else {
-                    panic!("Binding {} missing buffer", binding);
-                };
-                bind_group_entries.push(BindGroupEntry {
-                    binding: idx as u32,
-                    resource: buffer.buffer.as_entire_binding(),
-                });
-            }
+        for entry in entries.iter() {
+            let resource = if let Some(buffer) = self.buffers.get(&entry.resource) {
+                buffer.buffer.as_entire_binding()
+            } else if let Some(texture) = self.textures.get(&entry.resource) {
+                BindingResource::TextureView(&texture.view)
+            } else if let Some(sampler) = self.samplers.get(&entry.resource) {
+                BindingResource::Sampler(&sampler.sampler)
+            } else {
+                panic!("Binding {} missing resource", entry.resource);
+            };
+            bind_group_entries.push(BindGroupEntry {
+                binding: entry.binding,
+                resource,
+            });
         }
 
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        device.create_bind_group(&BindGroupDescriptor {
             label: None,
-            layout: &bind_group_layout,
+            layout,
             entries: bind_group_entries.as_slice(),
-        });
-
-        Ok((bind_group_layout, bind_group))
+        })
     }
 
     fn build_compute_pipeline(
-        &self,
-        pass: &RenderPass,
+        &mut self,
+        label: &Option<String>,
+        pipeline: &PassComputePipeline,
+        workgroups: &[Expression; 3],
         device: &Device,
         shaders: &HashMap<String, ShaderResource>,
     ) -> Result<PassResource, ResourceError> {
-        let (label, pipeline, workgroups) = match pass {
-            RenderPass::Compute {
-                label,
-                pipeline,
-                workgroups,
-            } => (label, pipeline, workgroups),
-            _ => panic!("how did we get here"),
-        };
-
-        let (bind_group_layout, bind_group) =
-            self.build_bind_group(label, Some(&pipeline.bindings), None, device)?;
+        let bindings_visibility = vec![ShaderStages::COMPUTE; pipeline.bindings.len()];
+        let bind_groups = self.build_bind_groups(
+            label,
+            &pipeline.bindings,
+            &bindings_visibility,
+            TextureUsage::Storage,
+            device,
+        )?;
 
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: label
                 .clone()
                 .map(|s| format!("{} (Pipeline Layout)", s))
                 .as_deref(),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &self.bind_group_layouts(&bind_groups),
             push_constant_ranges: &[],
         });
 
@@ -697,11 +1314,24 @@ impl Resources {
             entry_point: entry_point.deref(),
         });
 
+        let max_workgroups = device.limits().max_compute_workgroups_per_dimension;
+        let dispatch =
+            match Resources::evaluate_workgroups(workgroups, &self.variables, max_workgroups) {
+                Ok(dispatch) => dispatch,
+                Err(reason) => {
+                    return Err(ResourceError::InvalidResource {
+                        id: label.clone().unwrap_or("Compute Pipeline".to_string()),
+                        reason,
+                    })
+                }
+            };
+
         Ok(PassResource::Compute {
             label: label.clone(),
             pipeline: compute_pipeline,
-            bind_group,
-            workgroups: [workgroups[0], workgroups[1], workgroups[2]],
+            bind_groups,
+            workgroups: workgroups.clone(),
+            dispatch,
         })
     }
 
@@ -718,6 +1348,7 @@ impl Resources {
                 pipeline,
                 clear,
                 draw,
+                ..
             } => (label, pipeline, clear, draw),
             _ => panic!("how did we get here"),
         };
@@ -731,10 +1362,31 @@ impl Resources {
             });
         }
 
-        let (bind_group_layout, bind_group) = self.build_bind_group(
+        // only 1 and 4 samples are supported by every adapter
+        let mut samples = pipeline.samples.unwrap_or(1);
+        if samples != 1 && samples != 4 {
+            return Err(ResourceError::InvalidResource {
+                id: label.clone().unwrap_or("Render Pipeline".to_string()),
+                reason: format!("Unsupported sample count {}, expected 1 or 4", samples),
+            });
+        }
+        if let Some(max_samples) = self.options.max_samples {
+            if samples > max_samples {
+                samples = 1;
+            }
+        }
+
+        let bindings_visibility: Vec<ShaderStages> = pipeline
+            .bindings_visibility
+            .iter()
+            .flatten()
+            .map(|visibility| visibility.as_wgpu())
+            .collect();
+        let bind_groups = self.build_bind_groups(
             label,
-            pipeline.bindings.as_ref(),
-            pipeline.bindings_visibility.as_ref(),
+            pipeline.bindings.as_deref().unwrap_or_default(),
+            &bindings_visibility,
+            TextureUsage::Sampled,
             device,
         )?;
 
@@ -743,7 +1395,7 @@ impl Resources {
                 .clone()
                 .map(|s| format!("{} (Pipeline Layout)", s))
                 .as_deref(),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &self.bind_group_layouts(&bind_groups),
             push_constant_ranges: &[],
         });
 
@@ -767,7 +1419,12 @@ impl Resources {
 
         let targets = [Some(ColorTargetState {
             format,
-            blend: Some(BlendState::REPLACE),
+            blend: Some(
+                pipeline
+                    .blend
+                    .as_ref()
+                    .map_or(BlendState::REPLACE, |blend| blend.as_wgpu()),
+            ),
             write_mask: ColorWrites::ALL,
         })];
 
@@ -810,7 +1467,7 @@ impl Resources {
             },
             depth_stencil: None,
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: samples,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -819,12 +1476,28 @@ impl Resources {
 
         // TODO: ensure our Draw params a) match vertex layout and b) are valid resources
 
+        if samples > 1 && !self.multisample_targets.contains_key(&samples) {
+            let target = self.create_multisample_target(device, samples);
+            self.multisample_targets.insert(samples, target);
+        }
+
+        let clear = match clear {
+            Some(clear) => Some(clear.colour(format.is_srgb()).map_err(|error| {
+                ResourceError::InvalidResource {
+                    id: label.clone().unwrap_or_else(|| "render pass".to_string()),
+                    reason: format!("Invalid clear colour: {:?}", error),
+                }
+            })?),
+            None => None,
+        };
+
         Ok(PassResource::Render {
             label: label.clone(),
             pipeline: render_pipeline,
-            bind_group,
-            clear: clear.clone(),
+            bind_groups,
+            clear,
             draw: draw.clone(),
+            samples,
         })
     }
 
@@ -835,36 +1508,39 @@ impl Resources {
         format: TextureFormat,
         shaders: &HashMap<String, ShaderResource>,
     ) -> Result<PassResource, ResourceError> {
-        let (label, source, additional_bindings) = match pass {
+        let (label, source, additional_bindings, blend) = match pass {
             RenderPass::ShaderToy {
                 label,
                 source,
                 bindings,
-            } => (label, source, bindings),
+                blend,
+                ..
+            } => (label, source, bindings, blend),
             _ => panic!("how did we get here"),
         };
 
-        let mut bindings = vec!["shadertoy".to_string()];
+        let mut bindings = vec![Binding::Resource("shadertoy".to_string())];
         if let Some(additional_bindings) = additional_bindings {
             for binding in additional_bindings {
                 bindings.push(binding.to_owned());
             }
         }
 
-        let bindings_visibility = bindings
-            .iter()
-            .map(|_| RenderPipelineBindingVisibility::Fragment)
-            .collect();
-
-        let (bind_group_layout, bind_group) =
-            self.build_bind_group(label, Some(&bindings), Some(&bindings_visibility), device)?;
+        let bindings_visibility = vec![ShaderStages::FRAGMENT; bindings.len()];
+        let bind_groups = self.build_bind_groups(
+            label,
+            &bindings,
+            &bindings_visibility,
+            TextureUsage::Sampled,
+            device,
+        )?;
 
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: label
                 .clone()
                 .map(|s| format!("{} (Pipeline Layout)", s))
                 .as_deref(),
-            bind_group_layouts: &[&bind_group_layout],
+            bind_group_layouts: &self.bind_group_layouts(&bind_groups),
             push_constant_ranges: &[],
         });
 
@@ -879,7 +1555,11 @@ impl Resources {
 
         let targets = [Some(ColorTargetState {
             format,
-            blend: Some(BlendState::REPLACE),
+            blend: Some(
+                blend
+                    .as_ref()
+                    .map_or(BlendState::REPLACE, |blend| blend.as_wgpu()),
+            ),
             write_mask: ColorWrites::ALL,
         })];
 
@@ -907,6 +1587,7 @@ impl Resources {
             }),
             vertex_count: Some(6),
             storage: None,
+            size: None,
         };
 
         self.buffers
@@ -962,11 +1643,24 @@ impl Resources {
         Ok(PassResource::ShaderToy {
             label: label.clone(),
             pipeline: render_pipeline,
-            bind_group,
+            bind_groups,
         })
     }
 
-    pub fn update_setting(&mut self, key: String, value: SettingValue) {
+    pub fn update_setting(&mut self, device: &Device, key: String, value: SettingValue) {
+        // show the change, even when frozen
+        self.frozen_frame = false;
+        for init in self.init_passes.iter_mut() {
+            if init.reset_on.contains(&key) {
+                init.pending = true;
+            }
+        }
+
+        if let Some(number) = value.as_f64() {
+            self.variables.insert(key.clone(), number);
+            self.update_expressions(device);
+        }
+
         if let Some(uniform_id) = self.setting_lookup.get(&key) {
             // update data in uniform
             let (content, offsets) = match self.uniforms.get_mut(uniform_id) {
@@ -993,12 +1687,402 @@ impl Resources {
         }
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
+    // wgpu reports shader and pipeline errors asynchronously, so catch them here instead.
+    // lets the caller keep the scene it had when a new version fails to compile
+    pub async fn new_checked(
+        scene: &Scene,
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        options: &RenderOptions,
+    ) -> Result<Resources, ResourceError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let resources = Resources::new(scene, device, width, height, format, options);
+        match device.pop_error_scope().await {
+            Some(error) => Err(ResourceError::Validation(error.to_string())),
+            None => resources,
+        }
+    }
+
+    // times each pass on the gpu, if the device supports timestamp queries
+    pub fn set_profiling(&mut self, device: &Device, queue: &Queue, enabled: bool) {
+        if !enabled {
+            self.timer = None;
+        } else if self.timer.is_none() {
+            self.timer = GpuTimer::new(device, queue);
+        }
+    }
+
+    // None without profiling. call after the frame is submitted, times arrive a frame or two late
+    pub fn read_pass_times(&mut self, device: &Device) -> Option<Vec<PassTime>> {
+        self.timer.as_mut().map(|timer| timer.read(device))
+    }
+
+    // estimated from the size of each buffer and texture the scene created
+    pub fn memory_usage(&self) -> Vec<ResourceMemory> {
+        let mut memory: Vec<ResourceMemory> = self
+            .buffers
+            .iter()
+            .map(|(id, buffer)| ResourceMemory {
+                label: id.clone(),
+                bytes: buffer.buffer.size(),
+            })
+            .collect();
+
+        for (id, texture) in self.textures.iter() {
+            let texture = &texture.texture;
+            let pixel_bytes = texture.format().block_size(None).unwrap_or(4) as u64;
+            memory.push(ResourceMemory {
+                label: id.clone(),
+                bytes: texture.width() as u64 * texture.height() as u64 * pixel_bytes,
+            });
+        }
+
+        let pixel_bytes = self.format.block_size(None).unwrap_or(4) as u64;
+        for samples in self.multisample_targets.keys() {
+            memory.push(ResourceMemory {
+                label: format!("Multisample target (x{})", samples),
+                bytes: self.width as u64 * self.height as u64 * *samples as u64 * pixel_bytes,
+            });
+        }
+
+        if let Some(post_process) = self.post_process.as_ref() {
+            memory.push(ResourceMemory {
+                label: "Post-processing".to_string(),
+                bytes: post_process.memory_bytes(),
+            });
+        }
+
+        memory.sort_by_key(|memory| std::cmp::Reverse(memory.bytes));
+        memory
+    }
+
+    // the size the scene is rendered at, which shadertoy resolution should match
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    pub fn set_render_scale(&mut self, device: &Device, scale: f32) {
+        let scale = scale.clamp(MIN_RENDER_SCALE, 1.0);
+        if scale == self.render_scale {
+            return;
+        }
+        self.render_scale = scale;
+        self.ensure_post_process(device);
+
+        self.resize(device, self.output_width, self.output_height);
+    }
+
+    // a stage without effects, which just copies its input to the output
+    fn ensure_post_process(&mut self, device: &Device) {
+        if self.post_process.is_none() {
+            self.post_process = Some(
+                PostProcess::new(
+                    device,
+                    &[],
+                    &HashMap::new(),
+                    self.format,
+                    self.format,
+                    self.width,
+                    self.height,
+                )
+                .expect("Post-process stage without effects failed to build"),
+            );
+        }
+    }
+
+    // a frozen scene keeps showing its last frame without running any passes.
+    // the frame is held in the post-processing input, so it can be drawn again under the ui
+    pub fn set_frozen(&mut self, device: &Device, frozen: bool) {
+        if frozen == self.frozen {
+            return;
+        }
+        self.frozen = frozen;
+        self.frozen_frame = false;
+
+        if frozen {
+            self.ensure_post_process(device);
+        }
+    }
+
+    fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+        (
+            ((width as f32 * scale).round() as u32).max(1),
+            ((height as f32 * scale).round() as u32).max(1),
+        )
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        self.output_width = width;
+        self.output_height = height;
+        self.frozen_frame = false;
+        let (width, height) = Resources::scaled_size(width, height, self.render_scale);
+
         for (_, camera) in self.cameras.iter_mut() {
             camera.camera.resize(width, height);
             camera.matrix.update_view_proj(&camera.camera);
             camera.dirty = true;
         }
+
+        self.variables.insert("width".to_string(), width as f64);
+        self.variables.insert("height".to_string(), height as f64);
+        self.update_expressions(device);
+
+        self.width = width;
+        self.height = height;
+        let sample_counts: Vec<u32> = self.multisample_targets.keys().copied().collect();
+        for samples in sample_counts {
+            let target = self.create_multisample_target(device, samples);
+            self.multisample_targets.insert(samples, target);
+        }
+
+        if let Some(post_process) = self.post_process.as_mut() {
+            post_process.resize(device, width, height);
+        }
+    }
+
+    fn create_multisample_target(&self, device: &Device, samples: u32) -> TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multisample Target"),
+            size: wgpu::Extent3d {
+                width: self.width.max(1),
+                height: self.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn build_variables(scene: &Scene, width: u32, height: u32) -> HashMap<String, f64> {
+        let mut variables = HashMap::new();
+
+        for (key, value) in scene.settings.iter() {
+            if let Some(value) = value.as_f64() {
+                variables.insert(key.clone(), value);
+            }
+        }
+
+        variables.insert("width".to_string(), width as f64);
+        variables.insert("height".to_string(), height as f64);
+
+        variables
+    }
+
+    fn evaluate_buffer_size(
+        expression: &Expression,
+        variables: &HashMap<String, f64>,
+    ) -> Result<u64, String> {
+        let size = match expression.evaluate(variables) {
+            Ok(size) => size.ceil(),
+            Err(error) => return Err(format!("Invalid buffer size {:?}", error)),
+        };
+
+        if !size.is_finite() || size < 1.0 {
+            return Err(format!("Buffer size {} must be positive", size));
+        }
+
+        Ok(size as u64)
+    }
+
+    fn evaluate_texture_size(
+        width: &Expression,
+        height: &Expression,
+        variables: &HashMap<String, f64>,
+    ) -> Result<(u32, u32), String> {
+        let mut size = [0; 2];
+
+        for (idx, expression) in [width, height].iter().enumerate() {
+            let length = match expression.evaluate(variables) {
+                Ok(length) => length.ceil(),
+                Err(error) => return Err(format!("Invalid texture size {:?}", error)),
+            };
+
+            if !length.is_finite() || length < 1.0 {
+                return Err(format!("Texture size {} must be positive", length));
+            }
+
+            size[idx] = length as u32;
+        }
+
+        Ok((size[0], size[1]))
+    }
+
+    fn create_storage_texture(
+        device: &Device,
+        label: &Option<String>,
+        format: TextureStorageFormat,
+        (width, height): (u32, u32),
+    ) -> (Texture, TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: label.as_deref(),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: format.as_wgpu(),
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    fn evaluate_workgroups(
+        workgroups: &[Expression; 3],
+        variables: &HashMap<String, f64>,
+        max_workgroups: u32,
+    ) -> Result<[u32; 3], String> {
+        let mut dispatch = [0; 3];
+
+        for (idx, expression) in workgroups.iter().enumerate() {
+            let count = match expression.evaluate(variables) {
+                Ok(count) => count.ceil(),
+                Err(error) => return Err(format!("Invalid workgroup count {:?}", error)),
+            };
+
+            if !count.is_finite() || count < 0.0 {
+                return Err(format!("Workgroup count {} must not be negative", count));
+            }
+            // wgpu panics on dispatches above the device limit
+            if count > max_workgroups as f64 {
+                return Err(format!(
+                    "Workgroup count {} is above the device limit of {}",
+                    count, max_workgroups
+                ));
+            }
+
+            dispatch[idx] = count as u32;
+        }
+
+        Ok(dispatch)
+    }
+
+    // re-evaluate buffer sizes and dispatch sizes after the resolution or a setting changed
+    fn update_expressions(&mut self, device: &Device) {
+        let mut recreated = Vec::new();
+        let max_workgroups = device.limits().max_compute_workgroups_per_dimension;
+
+        for (id, buffer) in self.buffers.iter_mut() {
+            let size = match buffer.size.as_mut() {
+                Some(size) => size,
+                None => continue,
+            };
+
+            let bytes = match Resources::evaluate_buffer_size(&size.expression, &self.variables) {
+                Ok(bytes) => bytes,
+                Err(reason) => {
+                    eprintln!("Failed to resize buffer {}: {}", id, reason);
+                    continue;
+                }
+            };
+
+            if bytes == size.bytes {
+                continue;
+            }
+
+            buffer.buffer = device.create_buffer(&BufferDescriptor {
+                label: size.label.as_deref(),
+                size: bytes,
+                usage: size.usage,
+                mapped_at_creation: false,
+            });
+            buffer.vertex_count = buffer
+                .vertex
+                .as_ref()
+                .map(|v| (bytes as usize / v.stride) as u32);
+            size.bytes = bytes;
+            recreated.push(id.clone());
+        }
+
+        for (id, texture) in self.textures.iter_mut() {
+            let size = match Resources::evaluate_texture_size(
+                &texture.width,
+                &texture.height,
+                &self.variables,
+            ) {
+                Ok(size) => size,
+                Err(reason) => {
+                    eprintln!("Failed to resize texture {}: {}", id, reason);
+                    continue;
+                }
+            };
+
+            if size == texture.size {
+                continue;
+            }
+
+            (texture.texture, texture.view) =
+                Resources::create_storage_texture(device, &texture.label, texture.format, size);
+            texture.size = size;
+            recreated.push(id.clone());
+        }
+
+        for pass in self
+            .passes
+            .iter_mut()
+            .chain(self.init_passes.iter_mut().map(|init| &mut init.pass))
+        {
+            if let PassResource::Compute {
+                label,
+                workgroups,
+                dispatch,
+                ..
+            } = pass
+            {
+                match Resources::evaluate_workgroups(workgroups, &self.variables, max_workgroups) {
+                    Ok(workgroups) => *dispatch = workgroups,
+                    // the previous dispatch is kept
+                    Err(reason) => eprintln!(
+                        "Failed to update workgroups of {}: {}",
+                        label.as_deref().unwrap_or("compute pass"),
+                        reason
+                    ),
+                }
+            }
+        }
+
+        if !recreated.is_empty() {
+            // recreated buffers are empty, so seed them again
+            for init in self.init_passes.iter_mut() {
+                if recreated.contains(&init.buffer) {
+                    init.pending = true;
+                }
+            }
+
+            for index in 0..self.bind_groups.len() {
+                let bind_group = &self.bind_groups[index];
+                if !bind_group
+                    .entries
+                    .iter()
+                    .any(|entry| recreated.contains(&entry.resource))
+                {
+                    continue;
+                }
+
+                let rebuilt =
+                    self.create_bind_group(&bind_group.layout, &bind_group.entries, device);
+                self.bind_groups[index].bind_group = rebuilt;
+            }
+        }
     }
 
     pub fn render(
@@ -1009,6 +2093,21 @@ impl Resources {
         time: Time,
         shadertoy: ShaderToy,
     ) {
+        // with post-processing the scene is drawn into the stage's input instead of the view
+        let output = view;
+        let view = match self.post_process.as_ref() {
+            Some(post_process) => post_process.input(),
+            None => output,
+        };
+
+        if self.frozen && self.frozen_frame {
+            if let Some(post_process) = self.post_process.as_mut() {
+                post_process.apply(queue, encoder, output, time);
+            }
+            return;
+        }
+        self.frozen_frame = self.frozen;
+
         if let Some(time_buffer) = self.buffers.get(&"time".to_string()) {
             queue.write_buffer(&time_buffer.buffer, 0, bytemuck::cast_slice(&[time]));
         }
@@ -1047,58 +2146,107 @@ impl Resources {
             queue.write_buffer(&buffer.buffer, 0, content.as_ref());
         }
 
-        for pass in self.passes.iter() {
-            match pass {
-                PassResource::Compute {
-                    label,
-                    pipeline,
-                    bind_group,
-                    workgroups,
-                } => {
-                    if let Some(label) = label {
-                        encoder.push_debug_group(label);
-                    }
-                    let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                        label: Some("Vertex Compute Pass"),
-                    });
+        let mut initialised = false;
+        for init in self.init_passes.iter_mut().filter(|init| init.pending) {
+            Resources::encode_compute_pass(&init.pass, &self.bind_groups, encoder);
+            init.pending = false;
+            initialised = true;
+        }
 
-                    cpass.set_pipeline(pipeline);
-                    cpass.set_bind_group(0, bind_group, &[]);
-                    cpass.dispatch_workgroups(workgroups[0], workgroups[1], workgroups[2]);
+        // simulations are warmed up again after being reset
+        if initialised {
+            self.warmup_remaining = self.warmup;
+        }
 
-                    drop(cpass);
-                    if label.is_some() {
-                        encoder.pop_debug_group();
-                    }
+        for _ in 0..self.warmup_remaining {
+            for (pass, timing) in self.passes.iter().zip(self.schedules.iter()) {
+                if timing.enabled(&self.variables) {
+                    Resources::encode_compute_pass(pass, &self.bind_groups, encoder);
+                }
+            }
+        }
+        self.warmup_remaining = 0;
+
+        if let Some(timer) = self.timer.as_mut() {
+            timer.begin_frame();
+        }
+
+        // targets are cleared by the first pass drawing into them each frame
+        let mut view_cleared = false;
+        let mut multisample_cleared = Vec::new();
+
+        for (index, (pass, timing)) in self
+            .passes
+            .iter()
+            .zip(self.schedules.iter_mut())
+            .enumerate()
+        {
+            if !timing.ready(&self.variables, time.time) {
+                continue;
+            }
+
+            let timed = match self.timer.as_mut() {
+                Some(timer) => timer.start(
+                    encoder,
+                    pass.label()
+                        .map_or_else(|| format!("Pass {}", index), str::to_string),
+                ),
+                None => false,
+            };
+
+            match pass {
+                PassResource::Compute { .. } => {
+                    Resources::encode_compute_pass(pass, &self.bind_groups, encoder)
                 }
                 PassResource::Render {
                     label,
                     pipeline,
-                    bind_group,
-                    clear: _,
+                    bind_groups,
+                    clear,
                     draw,
+                    samples,
                 } => {
                     if let Some(label) = label {
                         encoder.push_debug_group(label);
                     }
 
-                    // todo: load from clear
+                    // multisampled passes draw into their own target and resolve into the view
+                    let (target, resolve_target, cleared) =
+                        match self.multisample_targets.get(samples) {
+                            Some(multisample_view) => (
+                                multisample_view,
+                                Some(view),
+                                multisample_cleared.contains(samples),
+                            ),
+                            None => (view, None, view_cleared),
+                        };
+                    // later passes draw over the earlier ones
+                    let load = if cleared {
+                        wgpu::LoadOp::Load
+                    } else {
+                        wgpu::LoadOp::Clear(clear.unwrap_or(wgpu::Color::BLACK))
+                    };
+                    if resolve_target.is_some() {
+                        multisample_cleared.push(*samples);
+                    }
+                    view_cleared = true;
+
                     let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         label: Some("Scene Pass"),
                         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                                store: true,
-                            },
+                            view: target,
+                            resolve_target,
+                            ops: wgpu::Operations { load, store: true },
                         })],
                         depth_stencil_attachment: None,
                     });
 
                     rpass.set_pipeline(pipeline);
                     for draw in draw {
-                        rpass.set_bind_group(0, bind_group, &[]);
+                        for (group, index) in bind_groups.iter().enumerate() {
+                            let bind_group = &self.bind_groups[*index].bind_group;
+                            rpass.set_bind_group(group as u32, bind_group, &[]);
+                        }
                         let mut vertices = draw.vertex_count.unwrap_or(0);
                         let instances = draw.instances.unwrap_or(1);
 
@@ -1122,27 +2270,35 @@ impl Resources {
                 PassResource::ShaderToy {
                     label,
                     pipeline,
-                    bind_group,
+                    bind_groups,
                 } => {
                     if let Some(label) = label {
                         encoder.push_debug_group(label);
                     }
 
+                    // blended passes draw over the earlier passes
+                    let load = if view_cleared {
+                        wgpu::LoadOp::Load
+                    } else {
+                        wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+                    };
+                    view_cleared = true;
+
                     let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                         label: Some("Scene Pass"),
                         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                             view,
                             resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                                store: true,
-                            },
+                            ops: wgpu::Operations { load, store: true },
                         })],
                         depth_stencil_attachment: None,
                     });
 
                     rpass.set_pipeline(pipeline);
-                    rpass.set_bind_group(0, bind_group, &[]);
+                    for (group, index) in bind_groups.iter().enumerate() {
+                        let bind_group = &self.bind_groups[*index].bind_group;
+                        rpass.set_bind_group(group as u32, bind_group, &[]);
+                    }
 
                     let vertex_buffer = self.buffers.get("shadertoy_quad").unwrap();
                     rpass.set_vertex_buffer(0, vertex_buffer.buffer.slice(..));
@@ -1158,6 +2314,80 @@ impl Resources {
                     }
                 }
             }
+
+            if timed {
+                if let Some(timer) = self.timer.as_mut() {
+                    timer.end(encoder);
+                }
+            }
+        }
+
+        // every drawing pass can be disabled, which would leave the view undefined
+        if !view_cleared {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Scene Clear"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+
+        if let Some(post_process) = self.post_process.as_mut() {
+            let timed = match self.timer.as_mut() {
+                Some(timer) => timer.start(encoder, "Post-processing".to_string()),
+                None => false,
+            };
+
+            post_process.apply(queue, encoder, output, time);
+
+            if timed {
+                if let Some(timer) = self.timer.as_mut() {
+                    timer.end(encoder);
+                }
+            }
+        }
+
+        if let Some(timer) = self.timer.as_mut() {
+            timer.resolve(encoder);
+        }
+    }
+
+    fn encode_compute_pass(
+        pass: &PassResource,
+        bind_groups: &[SharedBindGroup],
+        encoder: &mut CommandEncoder,
+    ) {
+        if let PassResource::Compute {
+            label,
+            pipeline,
+            bind_groups: pass_bind_groups,
+            dispatch,
+            ..
+        } = pass
+        {
+            if let Some(label) = label {
+                encoder.push_debug_group(label);
+            }
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Vertex Compute Pass"),
+            });
+
+            cpass.set_pipeline(pipeline);
+            for (group, index) in pass_bind_groups.iter().enumerate() {
+                cpass.set_bind_group(group as u32, &bind_groups[*index].bind_group, &[]);
+            }
+            cpass.dispatch_workgroups(dispatch[0], dispatch[1], dispatch[2]);
+
+            drop(cpass);
+            if label.is_some() {
+                encoder.pop_debug_group();
+            }
         }
     }
 }
@@ -71,14 +71,14 @@ impl Settings {
         let parent = if let Some(path) = path.parent() {
             path
         } else {
-            panic!("Failed to find parent of {:?}", path);
+            return Err(format!("Failed to find parent of {:?}", path));
         };
 
         if let Err(err) = fs::create_dir_all(parent) {
-            panic!(
+            return Err(format!(
                 "Failed to create settings directory {:?}, error: {:?}",
-                err, parent
-            );
+                parent, err
+            ));
         }
 
         let settings_string = match toml::to_string(&self.data) {
@@ -104,6 +104,17 @@ impl Settings {
         self.data.iter()
     }
 
+    // keeps the values of an older version of the scene, for settings that still match
+    pub fn merge(&mut self, other: &Settings) {
+        for (key, value) in other.data.iter() {
+            if let Some(current) = self.data.get_mut(key) {
+                if std::mem::discriminant(current) == std::mem::discriminant(value) {
+                    *current = value.clone();
+                }
+            }
+        }
+    }
+
     pub fn reset(&mut self, descriptor: &Descriptor) -> Result<(), SettingParseError> {
         for (key, setting) in descriptor.settings.iter() {
             let setting = SettingValue::from_descriptor(setting)?;
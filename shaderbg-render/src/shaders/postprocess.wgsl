@@ -29,14 +29,116 @@ fn vs_main(
     return out;
 }
 
+struct Effect {
+    params: vec4<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+};
+
 @group(0) @binding(0)
 var t_diffuse: texture_2d<f32>;
 @group(0)@binding(1)
 var s_diffuse: sampler;
+@group(0) @binding(2)
+var<uniform> effect: Effect;
+@group(0) @binding(3)
+var t_lut: texture_3d<f32>;
+
+@fragment
+fn blit(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+}
 
+// params.x = power
 @fragment
-fn postprocess_to_srgb(in: VertexOutput) -> @location(0) vec4<f32> {
+fn gamma(in: VertexOutput) -> @location(0) vec4<f32> {
     let colour = textureSample(t_diffuse, s_diffuse, in.tex_coords);
-    let srgb_colour = pow(colour, vec4<f32>(1.8));
-    return srgb_colour;
-}
\ No newline at end of file
+    return vec4<f32>(pow(colour.rgb, vec3<f32>(effect.params.x)), colour.a);
+}
+
+// params.x = threshold, params.y = intensity, params.z = radius in pixels
+@fragment
+fn bloom(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSampleLevel(t_diffuse, s_diffuse, in.tex_coords, 0.0);
+    let spacing = effect.params.z / (3.0 * effect.resolution);
+
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var x = -3; x <= 3; x += 1) {
+        for (var y = -3; y <= 3; y += 1) {
+            let offset = vec2<f32>(f32(x), f32(y));
+            let weight = exp(-dot(offset, offset) / 8.0);
+            let texel = textureSampleLevel(t_diffuse, s_diffuse, in.tex_coords + offset * spacing, 0.0);
+            glow += max(texel.rgb - vec3<f32>(effect.params.x), vec3<f32>(0.0)) * weight;
+            total += weight;
+        }
+    }
+
+    return vec4<f32>(colour.rgb + glow / total * effect.params.y, colour.a);
+}
+
+// params.x = radius in pixels, params.zw = direction
+@fragment
+fn blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    let spacing = effect.params.x / (4.0 * effect.resolution) * effect.params.zw;
+
+    var colour = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -4; i <= 4; i += 1) {
+        let weight = exp(-f32(i * i) / 8.0);
+        colour += textureSampleLevel(t_diffuse, s_diffuse, in.tex_coords + f32(i) * spacing, 0.0) * weight;
+        total += weight;
+    }
+
+    return colour / total;
+}
+
+// params.x = strength, params.y = radius, params.z = softness
+@fragment
+fn vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    // 0.0 at the centre, 1.0 in the corners
+    let from_centre = length(in.tex_coords - vec2<f32>(0.5)) * 1.41421356;
+    let edge = smoothstep(effect.params.y, effect.params.y + effect.params.z, from_centre);
+    return vec4<f32>(colour.rgb * (1.0 - effect.params.x * edge), colour.a);
+}
+
+// params.x = amount
+@fragment
+fn grain(in: VertexOutput) -> @location(0) vec4<f32> {
+    let colour = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let seed = in.clip_position.xy + vec2<f32>(fract(effect.time) * 100.0);
+    let noise = fract(sin(dot(seed, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    return vec4<f32>(colour.rgb + (noise - 0.5) * effect.params.x, colour.a);
+}
+
+// params.x = strength, params.y = lut size, params.z = 1 when the input is srgb
+@fragment
+fn lut(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sampled = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let srgb = effect.params.z > 0.5;
+    var colour = clamp(sampled.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+    if (srgb) {
+        colour = linear_to_srgb(colour);
+    }
+    let size = effect.params.y;
+    // sample the centre of the outer texels so the table's end points are exact
+    let coords = colour * ((size - 1.0) / size) + 0.5 / size;
+    var graded = mix(colour, textureSampleLevel(t_lut, s_diffuse, coords, 0.0).rgb, effect.params.x);
+    if (srgb) {
+        graded = srgb_to_linear(graded);
+    }
+    return vec4<f32>(graded, sampled.a);
+}
+
+fn linear_to_srgb(colour: vec3<f32>) -> vec3<f32> {
+    let low = colour * 12.92;
+    let high = 1.055 * pow(colour, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, colour <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(colour: vec3<f32>) -> vec3<f32> {
+    let low = colour / 12.92;
+    let high = pow((colour + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, colour <= vec3<f32>(0.04045));
+}
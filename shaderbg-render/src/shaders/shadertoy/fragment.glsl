@@ -22,8 +22,21 @@ vec2 fragCoord = vec2(gl_FragCoord.x, iResolution.y - gl_FragCoord.y);
 // insert ShaderToy code here
 {{SOURCE}}
 
+// ShaderToy outputs srgb colours, which are decoded when the gpu will encode them again
+#ifdef OUTPUT_SRGB
+vec3 shaderbg_srgb_to_linear(vec3 colour)
+{
+    vec3 low = colour / 12.92;
+    vec3 high = pow((colour + 0.055) / 1.055, vec3(2.4));
+    return mix(high, low, step(colour, vec3(0.04045)));
+}
+#endif
+
 // call ShaderToy main
 void main()
 {
     mainImage(fragColor, fragCoord);
+#ifdef OUTPUT_SRGB
+    fragColor.rgb = shaderbg_srgb_to_linear(clamp(fragColor.rgb, 0.0, 1.0));
+#endif
 }
\ No newline at end of file
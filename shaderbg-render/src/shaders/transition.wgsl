@@ -0,0 +1,75 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+) -> VertexOutput {
+    var x = 0.0;
+    var y = 0.0;
+    var tex_coords = vec2<f32>(0.0, 1.0);
+    if (in_vertex_index == u32(0)) {
+        x = -1.0; y = -1.0;
+    } else if (in_vertex_index == u32(1) || in_vertex_index == u32(3)) {
+        x = -1.0; y =  1.0;
+        tex_coords = vec2<f32>(0.0, 0.0);
+    } else if (in_vertex_index == u32(2) || in_vertex_index == u32(5)) {
+        x =  1.0; y = -1.0;
+        tex_coords = vec2<f32>(1.0, 1.0);
+    } else if (in_vertex_index == u32(4)) {
+        x =  1.0; y =  1.0;
+        tex_coords = vec2<f32>(1.0, 0.0);
+    }
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    out.tex_coords = tex_coords;
+    return out;
+}
+
+struct Blend {
+    resolution: vec2<f32>,
+    progress: f32,
+};
+
+@group(0) @binding(0)
+var t_from: texture_2d<f32>;
+@group(0) @binding(1)
+var t_to: texture_2d<f32>;
+@group(0) @binding(2)
+var s_blend: sampler;
+@group(0) @binding(3)
+var<uniform> blend: Blend;
+
+@fragment
+fn crossfade(in: VertexOutput) -> @location(0) vec4<f32> {
+    let outgoing = textureSample(t_from, s_blend, in.tex_coords);
+    let incoming = textureSample(t_to, s_blend, in.tex_coords);
+    return mix(outgoing, incoming, blend.progress);
+}
+
+// left to right, with a soft edge
+@fragment
+fn wipe(in: VertexOutput) -> @location(0) vec4<f32> {
+    let outgoing = textureSample(t_from, s_blend, in.tex_coords);
+    let incoming = textureSample(t_to, s_blend, in.tex_coords);
+    let edge = 0.05;
+    let position = blend.progress * (1.0 + edge * 2.0) - edge;
+    let amount = 1.0 - smoothstep(position - edge, position + edge, in.tex_coords.x);
+    return mix(outgoing, incoming, amount);
+}
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+// pixels switch over one at a time, in a random order
+@fragment
+fn dissolve(in: VertexOutput) -> @location(0) vec4<f32> {
+    let outgoing = textureSample(t_from, s_blend, in.tex_coords);
+    let incoming = textureSample(t_to, s_blend, in.tex_coords);
+    let noise = hash(floor(in.tex_coords * blend.resolution));
+    return select(outgoing, incoming, noise < blend.progress);
+}
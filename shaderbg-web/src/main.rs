@@ -6,9 +6,9 @@ use shaderbg_render::{
     gfx::{
         self,
         buffer::{ShaderToy, Time},
-        Gfx, GfxContext,
+        AdapterOptions, Gfx, GfxContext,
     },
-    scene::{Resources, Scene},
+    scene::{RenderOptions, Resources, Scene},
 };
 use winit::{
     dpi::PhysicalSize,
@@ -107,7 +107,7 @@ async fn run() {
     #[cfg(target_family = "wasm")]
     wasm::insert_canvas(window.as_ref());
 
-    let gfx_context = GfxContext::new(window.as_ref());
+    let gfx_context = GfxContext::new(window.as_ref(), AdapterOptions::default());
 
     let size = window.inner_size();
     let mut gfx = Gfx::new(gfx_context, size.width, size.height, true).await;
@@ -134,6 +134,7 @@ async fn run() {
         gfx.config.width,
         gfx.config.height,
         gfx.config.format,
+        &RenderOptions::default(),
     )
     .unwrap();
     let mut last_frame = Instant::now();
@@ -145,7 +146,7 @@ async fn run() {
             .iter()
             .map(|(name, scene)| (name.clone(), scene))
             .collect(),
-        &gfx.device,
+        None,
     );
 
     let mut frame_times = VecDeque::new();
@@ -167,7 +168,7 @@ async fn run() {
                 ..
             } => {
                 gfx.resized(width, height);
-                resources.resize(width, height);
+                resources.resize(&gfx.device, width, height);
             }
             Event::WindowEvent {
                 event:
@@ -178,7 +179,7 @@ async fn run() {
                 ..
             } => {
                 gfx.resized(*width, *height);
-                resources.resize(*width, *height);
+                resources.resize(&gfx.device, *width, *height);
                 egui_platform.set_pixels_per_point(scale_factor as f32);
             }
             Event::UserEvent(ThemeEvent::Dark) => {
@@ -215,6 +216,12 @@ async fn run() {
                     &gfx.queue,
                     &mut gfx.device,
                     time,
+                    |name| {
+                        scenes
+                            .iter()
+                            .find(|(scene_name, _)| scene_name == name)
+                            .map(|(_, scene)| scene)
+                    },
                 );
 
                 let mut changes = Vec::new();
@@ -243,10 +250,13 @@ async fn run() {
                                 .open(&mut win_open)
                                 .resizable(false)
                                 .show(ctx, |ui| {
+                                    // nowhere to save presets on the web, so they can only be applied
                                     open = scene_ui.render(
                                         ui,
                                         &scenes[current_scene].1.settings,
+                                        &scenes[current_scene].1.presets,
                                         &mut changes,
+                                        None,
                                     ) == gfx::ui::SceneUiResult::Open;
                                 });
                         }
@@ -264,7 +274,7 @@ async fn run() {
 
                 for (key, value) in changes {
                     scenes[current_scene].1.settings.update(&key, value.clone());
-                    resources.update_setting(key, value);
+                    resources.update_setting(&gfx.device, key, value);
                 }
 
                 if let Some(next_scene) = next_scene {
@@ -275,6 +285,7 @@ async fn run() {
                         gfx.config.width,
                         gfx.config.height,
                         gfx.config.format,
+                        &RenderOptions::default(),
                     )
                     .unwrap();
                     if scene_ui.is_some() {
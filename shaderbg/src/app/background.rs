@@ -8,9 +8,12 @@ use tao::{
     window::{Window, WindowBuilder},
 };
 
-use crate::app::{AppEvent, AppState, ThreadEvent};
+use crate::{
+    app::{AppEvent, AppState, FrameLimiter, Profiler, ThreadEvent},
+    io::FrameTarget,
+};
 use shaderbg_render::{
-    gfx::{buffer::ShaderToy, Gfx, GfxContext},
+    gfx::{buffer::ShaderToy, resolution::DynamicResolution, Gfx, GfxContext},
     scene::{io::setting::SettingValue, Resources},
 };
 
@@ -19,6 +22,8 @@ pub enum BackgroundEvent {
     TaoEvent(Event<'static, ThreadEvent>),
     SettingUpdated(String, SettingValue),
     SceneChanged,
+    SceneReloaded,
+    FrameRateChanged,
     Stop,
 }
 
@@ -264,7 +269,10 @@ impl Background {
                 .build(event_loop)
                 .unwrap();
 
-            gfx_context = Some(GfxContext::new(&windows_window));
+            gfx_context = Some(GfxContext::new(
+                &windows_window,
+                app_state.get().adapter_options(),
+            ));
             window = Some(windows_window);
         }
 
@@ -300,7 +308,10 @@ impl Background {
                 NSWindow::setFrame_display_(ns_window, rect, true);
             }
 
-            gfx_context = Some(GfxContext::new(&macos_window));
+            gfx_context = Some(GfxContext::new(
+                &macos_window,
+                app_state.get().adapter_options(),
+            ));
             window = Some(Box::new(macos_window));
         }
 
@@ -321,6 +332,41 @@ impl Background {
         }
     }
 
+    fn build_resources(&self, gfx: &Gfx) -> Option<Resources> {
+        let state = self.app_state.get();
+        let scene = state.scene()?;
+
+        // checked, so a reloaded scene that fails validation leaves the old one running
+        match pollster::block_on(Resources::new_checked(
+            scene,
+            &gfx.device,
+            gfx.config.width,
+            gfx.config.height,
+            gfx.config.format,
+            &state.config.background_render_options(),
+        )) {
+            Ok(resources) => Some(resources),
+            Err(error) => {
+                eprintln!("failed to load scene: {error:?}");
+                None
+            }
+        }
+    }
+
+    fn update_render_scale(
+        gfx: &Gfx,
+        resources: Option<&mut Resources>,
+        dynamic_resolution: Option<&mut DynamicResolution>,
+        frame_time: std::time::Duration,
+    ) {
+        if let (Some(resources), Some(dynamic_resolution)) = (resources, dynamic_resolution) {
+            let frame_ms = frame_time.as_secs_f32() * 1000.0;
+            if let Some(scale) = dynamic_resolution.update(frame_ms, resources.render_scale()) {
+                resources.set_render_scale(&gfx.device, scale);
+            }
+        }
+    }
+
     pub fn run(mut self, rx: mpsc::Receiver<BackgroundEvent>) {
         let size = self.window.inner_size();
         let mut gfx = pollster::block_on(Gfx::new(
@@ -331,20 +377,19 @@ impl Background {
         ));
         let mut shadertoy = ShaderToy::new();
 
-        let mut resources = if let Some(scene) = self.app_state.get().scene() {
-            Some(
-                Resources::new(
-                    scene,
-                    &gfx.device,
-                    gfx.config.width,
-                    gfx.config.height,
-                    gfx.config.format,
-                )
-                .unwrap(),
-            )
-        } else {
-            None
-        };
+        let mut resources = self.build_resources(&gfx);
+
+        let state = self.app_state.get();
+        let mut dynamic_resolution = state.config.render_scale.dynamic_resolution();
+        gfx.set_present_mode(state.config.frame_rate.present_mode.present_mode());
+        let mut frame_limiter = FrameLimiter::new(
+            state
+                .config
+                .frame_rate
+                .max_fps(FrameTarget::Background, state.scene()),
+        );
+        drop(state);
+        let mut profiler = Profiler::new(FrameTarget::Background);
 
         loop {
             if let Ok(event) = rx.recv() {
@@ -360,11 +405,45 @@ impl Background {
                             break;
                         }
                         Event::MainEventsCleared => self.window.request_redraw(),
-                        Event::RedrawEventsCleared => {
+                        Event::RedrawEventsCleared if frame_limiter.ready() => {
+                            // resources belong to the lost device, so rebuild them with the new one
+                            if gfx.device_lost() {
+                                match pollster::block_on(gfx.recover()) {
+                                    Ok(()) => resources = self.build_resources(&gfx),
+                                    Err(error) => {
+                                        eprintln!("failed to recover graphics device: {error:?}")
+                                    }
+                                }
+                            }
+
                             let time = { *self.app_state.get_time() };
                             let size = self.window.inner_size();
-                            shadertoy.update(time.time, time.dt as f64, size.width, size.height);
+                            let (width, height) = match resources.as_ref() {
+                                Some(resources) => resources.size(),
+                                None => (size.width, size.height),
+                            };
+                            shadertoy.update(time.time, time.dt as f64, width, height);
+
+                            let frozen = self.app_state.get().clock.frozen();
+                            if let Some(resources) = resources.as_mut() {
+                                resources.set_frozen(&gfx.device, frozen);
+                            }
+
+                            profiler.begin(&self.app_state, &gfx, resources.as_mut());
+                            let started = std::time::Instant::now();
                             gfx.render(resources.as_mut(), time, shadertoy, None, |_, _| {});
+                            profiler.end(
+                                &self.app_state,
+                                &gfx,
+                                resources.as_mut(),
+                                started.elapsed(),
+                            );
+                            Background::update_render_scale(
+                                &gfx,
+                                resources.as_mut(),
+                                dynamic_resolution.as_mut(),
+                                started.elapsed(),
+                            );
                         }
                         Event::WindowEvent {
                             event: WindowEvent::Resized(PhysicalSize { width, height }),
@@ -372,7 +451,7 @@ impl Background {
                         } => {
                             gfx.resized(width, height);
                             if let Some(resources) = resources.as_mut() {
-                                resources.resize(width, height);
+                                resources.resize(&gfx.device, width, height);
                             }
                         }
                         Event::WindowEvent {
@@ -385,31 +464,56 @@ impl Background {
                         } => {
                             gfx.resized(*width, *height);
                             if let Some(resources) = resources.as_mut() {
-                                resources.resize(*width, *height);
+                                resources.resize(&gfx.device, *width, *height);
                             }
                         }
                         _ => {}
                     },
                     BackgroundEvent::SettingUpdated(key, value) => {
                         if let Some(resources) = resources.as_mut() {
-                            resources.update_setting(key, value);
+                            resources.update_setting(&gfx.device, key, value);
                         }
                     }
                     BackgroundEvent::SceneChanged => {
-                        resources = if let Some(scene) = self.app_state.get().scene() {
-                            Some(
-                                Resources::new(
-                                    scene,
-                                    &gfx.device,
-                                    gfx.config.width,
-                                    gfx.config.height,
-                                    gfx.config.format,
-                                )
-                                .unwrap(),
-                            )
-                        } else {
-                            None
-                        };
+                        // keep drawing the old scene while the new one is built
+                        let old = resources.take();
+                        resources = self.build_resources(&gfx);
+                        let transition = self.app_state.get().config.transition.clone();
+                        if let (Some(old), Some(kind), Some(_)) =
+                            (old, transition.kind(), &resources)
+                        {
+                            gfx.start_transition(old, kind, transition.duration());
+                        }
+                        let state = self.app_state.get();
+                        dynamic_resolution = state.config.render_scale.dynamic_resolution();
+                        frame_limiter.set_max_fps(
+                            state
+                                .config
+                                .frame_rate
+                                .max_fps(FrameTarget::Background, state.scene()),
+                        );
+                    }
+                    BackgroundEvent::SceneReloaded => {
+                        // keep the old version running if the new one fails
+                        if let Some(reloaded) = self.build_resources(&gfx) {
+                            resources = Some(reloaded);
+                        }
+                        let state = self.app_state.get();
+                        dynamic_resolution = state.config.render_scale.dynamic_resolution();
+                        frame_limiter.set_max_fps(
+                            state
+                                .config
+                                .frame_rate
+                                .max_fps(FrameTarget::Background, state.scene()),
+                        );
+                    }
+                    BackgroundEvent::FrameRateChanged => {
+                        let state = self.app_state.get();
+                        let frame_rate = &state.config.frame_rate;
+                        gfx.set_present_mode(frame_rate.present_mode.present_mode());
+                        frame_limiter.set_max_fps(
+                            frame_rate.max_fps(FrameTarget::Background, state.scene()),
+                        );
                     }
                     BackgroundEvent::Stop => {
                         self.app_state
@@ -0,0 +1,78 @@
+/*
+ * The scene clock, owned by the app thread
+ * renderers read the time it produces through AppState::get_time
+ */
+
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 4.0;
+
+#[derive(Clone, Debug)]
+pub enum ClockEvent {
+    TogglePause,
+    Speed(f64),
+    // milliseconds since the scene started
+    Seek(u32),
+    // holds the current frame, stopping simulations as well as time
+    Freeze(bool),
+}
+
+pub struct Clock {
+    time_ms: f64,
+    speed: f64,
+    paused: bool,
+    frozen: bool,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            time_ms: 0.0,
+            speed: 1.0,
+            paused: false,
+            frozen: false,
+        }
+    }
+
+    // advances by dt seconds of real time, returning the scene time and scaled dt
+    pub fn tick(&mut self, dt: f64) -> (u32, f64) {
+        let dt = if self.paused || self.frozen {
+            0.0
+        } else {
+            dt * self.speed
+        };
+        self.time_ms += dt * 1000.0;
+
+        (self.now(), dt)
+    }
+
+    pub fn handle(&mut self, event: ClockEvent) {
+        match event {
+            ClockEvent::TogglePause => self.paused = !self.paused,
+            ClockEvent::Speed(speed) => self.speed = speed.clamp(MIN_SPEED, MAX_SPEED),
+            ClockEvent::Seek(time) => self.time_ms = time as f64,
+            ClockEvent::Freeze(frozen) => self.frozen = frozen,
+        }
+    }
+
+    pub fn now(&self) -> u32 {
+        self.time_ms as u32
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::new()
+    }
+}
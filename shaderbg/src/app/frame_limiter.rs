@@ -0,0 +1,50 @@
+/*
+ * Paces redraws to a maximum frame rate
+ */
+use std::time::{Duration, Instant};
+
+// the event loop can wake a little early, so frames due within this are drawn
+const TOLERANCE: Duration = Duration::from_millis(1);
+
+// 0 or no limit means uncapped
+pub fn frame_interval(max_fps: Option<u32>) -> Option<Duration> {
+    max_fps
+        .filter(|max_fps| *max_fps > 0)
+        .map(|max_fps| Duration::from_secs_f64(1.0 / max_fps as f64))
+}
+
+pub struct FrameLimiter {
+    interval: Option<Duration>,
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: Option<u32>) -> FrameLimiter {
+        FrameLimiter {
+            interval: frame_interval(max_fps),
+            next_frame: Instant::now(),
+        }
+    }
+
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.interval = frame_interval(max_fps);
+        self.next_frame = Instant::now();
+    }
+
+    // true when a frame should be drawn now
+    pub fn ready(&mut self) -> bool {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return true,
+        };
+
+        let now = Instant::now();
+        if now + TOLERANCE < self.next_frame {
+            return false;
+        }
+
+        // frames that were missed are skipped rather than caught up on
+        self.next_frame = (self.next_frame + interval).max(now);
+        true
+    }
+}
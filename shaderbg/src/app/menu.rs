@@ -9,7 +9,7 @@ use tao::{
     menu::{ContextMenu, MenuBar, MenuId, MenuItem, MenuItemAttributes as MenuButton, MenuType},
 };
 
-use super::{AppEvent, AppState, ThreadEvent, Windows};
+use super::{AppEvent, AppState, ClockEvent, PlaylistMessage, ThreadEvent, Windows};
 
 enum Menu {
     MenuBar(MenuBar),
@@ -163,6 +163,10 @@ impl MenuBuilder {
         items.insert(settigns_id, |menu, _, _| {
             menu.open_window(Windows::Settings);
         });
+        let playlists_id = menu_add(&mut menu, MenuButton::new("&Playlists"));
+        items.insert(playlists_id, |menu, _, _| {
+            menu.open_window(Windows::Playlists)
+        });
         let performance_id = menu_add(&mut menu, MenuButton::new("&Performance"));
         items.insert(performance_id, |menu, _, _| {
             menu.open_window(Windows::Performance)
@@ -172,7 +176,8 @@ impl MenuBuilder {
     }
 
     fn add_scene_menu(&mut self, mut menu: Menu) -> Menu {
-        if let Some(scene) = self.app_state.get().scene() {
+        let state = self.app_state.get();
+        if let Some(scene) = state.scene() {
             menu_add(
                 &mut menu,
                 MenuButton::new(
@@ -180,10 +185,29 @@ impl MenuBuilder {
                 )
                 .with_enabled(false),
             );
-            let pause_id = menu_add(&mut menu, MenuButton::new("&Pause"));
-            self.items_window.insert(pause_id, |_, _, _| {});
+            let items = match menu {
+                Menu::MenuBar(_) => &mut self.items_window,
+                Menu::ContextMenu(_) => &mut self.items_tray,
+            };
+            let pause_label = if state.clock.paused() {
+                "&Resume"
+            } else {
+                "&Pause"
+            };
+            let pause_id = menu_add(&mut menu, MenuButton::new(pause_label));
+            items.insert(pause_id, |menu, _, _| {
+                menu.app_state
+                    .send(AppEvent::Clock(ClockEvent::TogglePause))
+                    .unwrap();
+            });
             let reload_id = menu_add(&mut menu, MenuButton::new("&Reload"));
-            self.items_window.insert(reload_id, |_, _, _| {});
+            items.insert(reload_id, |menu, _, _| {
+                menu.app_state.send(AppEvent::RescanScenes).unwrap();
+            });
+            let screenshot_id = menu_add(&mut menu, MenuButton::new("&Save Screenshot"));
+            items.insert(screenshot_id, |menu, _, _| {
+                menu.app_state.send(AppEvent::SaveScreenshot).unwrap();
+            });
         } else {
             menu_add(
                 &mut menu,
@@ -276,6 +300,36 @@ impl MenuBuilder {
         menu
     }
 
+    // the current scene's presets, only shown in the tray
+    fn build_presets_menu(&mut self) -> ContextMenu {
+        let mut menu = ContextMenu::new();
+
+        let state = self.app_state.get();
+        let presets = match state.scene() {
+            Some(scene) if !scene.presets.is_empty() => &scene.presets,
+            _ => {
+                menu.add_item(MenuButton::new("No presets").with_enabled(false));
+                return menu;
+            }
+        };
+
+        for preset in presets.iter() {
+            let id = menu.add_item(MenuButton::new(preset.name.as_str())).id();
+            self.data_tray.insert(id, preset.name.clone());
+            self.items_tray.insert(id, |menu, _, id| {
+                if let Some(preset) = menu.data_tray.get(&id) {
+                    menu.app_state
+                        .send(AppEvent::ApplyPreset(preset.clone()))
+                        .unwrap();
+                } else {
+                    eprintln!("Preset {:?} did not store data", id);
+                }
+            });
+        }
+
+        menu
+    }
+
     pub fn build_tray_menu(&mut self) -> ContextMenu {
         self.items_tray.clear();
 
@@ -311,6 +365,21 @@ impl MenuBuilder {
 
         menu = menu_unwrap_contextmenu(self.add_scene_menu(Menu::ContextMenu(menu)));
 
+        let next_id = menu.add_item(MenuButton::new("Next Scene")).id();
+        self.items_tray.insert(next_id, |menu, _, _| {
+            menu.app_state
+                .send(AppEvent::Playlist(PlaylistMessage::Next))
+                .unwrap();
+        });
+        let previous_id = menu.add_item(MenuButton::new("Previous Scene")).id();
+        self.items_tray.insert(previous_id, |menu, _, _| {
+            menu.app_state
+                .send(AppEvent::Playlist(PlaylistMessage::Previous))
+                .unwrap();
+        });
+        let presets_menu = self.build_presets_menu();
+        menu.add_submenu("Presets", true, presets_menu);
+
         menu.add_native_item(MenuItem::Separator);
 
         let recent_scenes_menu =
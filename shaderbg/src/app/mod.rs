@@ -6,27 +6,37 @@
  *
  * the real main thread is WindowThread, which processes the Tao EventLoop
  */
-use std::{
-    sync::{mpsc, Arc, RwLock},
-    time::SystemTime,
-};
+use std::sync::{mpsc, Arc, RwLock};
 use tao::{event::Event, event_loop::EventLoopProxy};
 
 use crate::{
-    app::timer::TimerMessage,
+    app::{timer::TimerMessage, watcher::WatcherMessage},
+    export,
     io::{Args, Config, ConfigUpdate, TrayConfig},
 };
-use shaderbg_render::{gfx::buffer::Time, scene::io::setting::SettingValue};
+use shaderbg_render::{
+    gfx::{buffer::Time, ui::PresetAction, HeadlessGfx},
+    scene::{io::setting::SettingValue, Presets, Scene},
+};
 
 mod background;
+mod clock;
+mod frame_limiter;
 mod menu;
+mod playlist;
+mod profiler;
 mod state;
 mod thread;
 pub mod timer;
 mod tray;
+mod watcher;
 mod window;
 pub use background::*;
+pub use clock::*;
+pub use frame_limiter::*;
 pub use menu::*;
+pub use playlist::PlaylistMessage;
+pub use profiler::*;
 pub use state::*;
 pub use thread::*;
 pub use tray::*;
@@ -46,8 +56,15 @@ pub enum AppEvent {
     BackgroundClosed(bool),
     SceneSettingsSaved,
     SettingUpdated(String, SettingValue),
+    Preset(PresetAction),
+    ApplyPreset(String),
     ConfigUpdated(Box<[ConfigUpdate]>),
     SetScene(String),
+    SceneReloaded(String, Box<Scene>),
+    RescanScenes,
+    SaveScreenshot,
+    Clock(ClockEvent),
+    Playlist(PlaylistMessage),
 }
 
 #[derive(Clone, Debug)]
@@ -75,11 +92,15 @@ pub fn start_main(
     let mut background_handle: Option<std::thread::JoinHandle<()>> = None;
     let mut background_channel: Option<mpsc::Sender<BackgroundEvent>> = None;
 
-    let started = SystemTime::now();
-
     let (timer_tx, timer_rx) = mpsc::channel();
     let mut timer_handle = Some(timer::run(app_state.clone(), timer_rx));
 
+    let (watcher_tx, watcher_rx) = mpsc::channel();
+    let mut watcher_handle = Some(watcher::run(app_state.clone(), watcher_rx));
+
+    let (playlist_tx, playlist_rx) = mpsc::channel();
+    let mut playlist_handle = Some(playlist::run(app_state.clone(), playlist_rx));
+
     let handle = std::thread::spawn(move || {
         // let mut was_empty = None;
         loop {
@@ -112,6 +133,14 @@ pub fn start_main(
                     if let Some(handle) = timer_handle.take() {
                         handle.join().unwrap();
                     }
+                    watcher_tx.send(WatcherMessage::Quit).ok();
+                    if let Some(handle) = watcher_handle.take() {
+                        handle.join().unwrap();
+                    }
+                    playlist_tx.send(PlaylistMessage::Quit).ok();
+                    if let Some(handle) = playlist_handle.take() {
+                        handle.join().unwrap();
+                    }
                 }
                 AppEvent::WindowStateChange(value) => {
                     state.write().unwrap().window_open = value;
@@ -180,14 +209,27 @@ pub fn start_main(
                         .unwrap();
                 }
                 AppEvent::Update(dt) => {
-                    let now = SystemTime::now()
-                        .duration_since(started)
-                        .unwrap()
-                        .as_millis() as u32;
+                    let (now, dt) = state.write().unwrap().clock.tick(dt);
                     if let Ok(mut time) = time.write() {
                         time.update_time(now, dt);
                     }
                 }
+                AppEvent::Clock(event) => {
+                    // menus show pause or resume
+                    let rebuild_menus = matches!(event, ClockEvent::TogglePause);
+                    // seeking while paused has to show up straight away
+                    let now = {
+                        let mut state = state.write().unwrap();
+                        state.clock.handle(event);
+                        state.clock.now()
+                    };
+                    if let Ok(mut time) = time.write() {
+                        time.update_time(now, 0.0);
+                    }
+                    if rebuild_menus {
+                        proxy.send_event(ThreadEvent::RebuildMenus).unwrap();
+                    }
+                }
                 AppEvent::EventLoopReady => {
                     let (window_open, tray_open, background_open) = {
                         let state = app_state.get();
@@ -208,37 +250,50 @@ pub fn start_main(
                 }
                 AppEvent::SceneSettingsSaved => {
                     if let Ok(state) = state.read() {
-                        if let Some(scene) = state.scene() {
-                            scene
-                                .settings
-                                .save(
-                                    state
-                                        .config
-                                        .settings_dir
-                                        .join(format!("{}.toml", state.scene_name().unwrap())),
-                                )
-                                .unwrap();
-                        }
+                        save_scene_settings(&state);
                     }
                 }
                 AppEvent::SettingUpdated(key, setting) => {
+                    update_setting(&state, &proxy, background_channel.as_ref(), key, setting);
+                }
+                AppEvent::Preset(action) => {
                     if let Ok(mut state) = state.write() {
-                        let key = key.clone();
-                        let setting = setting.clone();
-                        if let Some(scene) = state.scene_mut() {
-                            scene.settings.update(&key, setting);
+                        if let Err(e) = update_presets(&mut state, action) {
+                            eprintln!("Failed to update presets: {}", e);
                         }
                     }
-                    proxy
-                        .send_event(ThreadEvent::SettingUpdated(key.clone(), setting.clone()))
-                        .unwrap();
-
-                    if let Some(background) = background_channel.as_ref() {
-                        background
-                            .send(BackgroundEvent::SettingUpdated(key, setting))
-                            .unwrap();
+                    proxy.send_event(ThreadEvent::RebuildMenus).unwrap();
+                }
+                AppEvent::ApplyPreset(name) => {
+                    let changes = state
+                        .read()
+                        .unwrap()
+                        .scene()
+                        .and_then(|scene| scene.presets.changes(&name));
+                    match changes {
+                        Some(changes) => {
+                            for (key, setting) in changes {
+                                update_setting(
+                                    &state,
+                                    &proxy,
+                                    background_channel.as_ref(),
+                                    key,
+                                    setting,
+                                );
+                            }
+                            if let Ok(state) = state.read() {
+                                save_scene_settings(&state);
+                            }
+                        }
+                        None => eprintln!("No preset named {} in the current scene", name),
                     }
                 }
+                AppEvent::SaveScreenshot => {
+                    let state = state.clone();
+                    let time = *time.read().unwrap();
+                    // rendered on its own thread, creating a device can take a while
+                    std::thread::spawn(move || save_screenshot(state, time));
+                }
                 AppEvent::SetScene(scene) => {
                     let changed = if let Ok(mut state) = state.write() {
                         if state.set_scene(scene.clone()) {
@@ -261,6 +316,42 @@ pub fn start_main(
                         }
                     }
                 }
+                AppEvent::SceneReloaded(name, scene) => {
+                    let current = state.write().unwrap().replace_scene(&name, *scene);
+
+                    proxy.send_event(ThreadEvent::RebuildMenus).unwrap();
+                    proxy
+                        .send_event(ThreadEvent::SceneReloaded(current))
+                        .unwrap();
+
+                    if current {
+                        if let Some(background) = background_channel.as_ref() {
+                            background.send(BackgroundEvent::SceneReloaded).unwrap();
+                        }
+                    }
+                }
+                AppEvent::Playlist(message) => {
+                    playlist_tx.send(message).ok();
+                }
+                AppEvent::RescanScenes => {
+                    let kept = state.write().unwrap().rescan_scenes();
+                    watcher_tx.send(WatcherMessage::Rescan).ok();
+
+                    proxy.send_event(ThreadEvent::RebuildMenus).unwrap();
+                    if kept {
+                        proxy.send_event(ThreadEvent::SceneReloaded(true)).unwrap();
+                        if let Some(background) = background_channel.as_ref() {
+                            background.send(BackgroundEvent::SceneReloaded).unwrap();
+                        }
+                    } else {
+                        // the current scene was removed
+                        proxy.send_event(ThreadEvent::SceneReloaded(false)).unwrap();
+                        proxy.send_event(ThreadEvent::SceneChanged).unwrap();
+                        if let Some(background) = background_channel.as_ref() {
+                            background.send(BackgroundEvent::SceneChanged).unwrap();
+                        }
+                    }
+                }
                 AppEvent::ConfigUpdated(updates) => {
                     if let Ok(mut state) = state.write() {
                         for update in updates.into_vec() {
@@ -269,6 +360,26 @@ pub fn start_main(
                                     .send_event(ThreadEvent::UpdateTheme(theme.clone()))
                                     .unwrap();
                             }
+                            // pipelines are rebuilt with the new render options
+                            if let ConfigUpdate::Antialiasing(_) | ConfigUpdate::PostProcess(_) =
+                                &update
+                            {
+                                proxy.send_event(ThreadEvent::SceneChanged).unwrap();
+                                if let Some(background) = background_channel.as_ref() {
+                                    background.send(BackgroundEvent::SceneChanged).unwrap();
+                                }
+                            }
+                            if let ConfigUpdate::RenderScale(_) = &update {
+                                if let Some(background) = background_channel.as_ref() {
+                                    background.send(BackgroundEvent::SceneChanged).unwrap();
+                                }
+                            }
+                            if let ConfigUpdate::FrameRate(_) = &update {
+                                proxy.send_event(ThreadEvent::FrameRateChanged).unwrap();
+                                if let Some(background) = background_channel.as_ref() {
+                                    background.send(BackgroundEvent::FrameRateChanged).unwrap();
+                                }
+                            }
                             state.config.update(update);
                         }
 
@@ -289,3 +400,84 @@ pub fn start_main(
 
     (return_state, handle)
 }
+
+fn update_setting(
+    state: &Arc<RwLock<State>>,
+    proxy: &EventLoopProxy<ThreadEvent>,
+    background_channel: Option<&mpsc::Sender<BackgroundEvent>>,
+    key: String,
+    setting: SettingValue,
+) {
+    if let Ok(mut state) = state.write() {
+        if let Some(scene) = state.scene_mut() {
+            scene.settings.update(&key, setting.clone());
+        }
+    }
+    proxy
+        .send_event(ThreadEvent::SettingUpdated(key.clone(), setting.clone()))
+        .unwrap();
+
+    if let Some(background) = background_channel {
+        background
+            .send(BackgroundEvent::SettingUpdated(key, setting))
+            .unwrap();
+    }
+}
+
+fn save_scene_settings(state: &State) {
+    if let (Some(scene), Some(name)) = (state.scene(), state.scene_name()) {
+        let path = state.config.settings_dir.join(format!("{}.toml", name));
+        if let Err(e) = scene.settings.save(path) {
+            eprintln!("Error saving scene settings: {}", e);
+        }
+    }
+}
+
+// user presets are saved as soon as they change
+fn update_presets(state: &mut State, action: PresetAction) -> Result<(), String> {
+    let name = state.scene_name().ok_or("No scene loaded")?.to_string();
+    let path = Presets::path(&state.config.settings_dir, &name);
+    let scene = state.scene_mut().ok_or("No scene loaded")?;
+
+    match action {
+        PresetAction::Save(preset) => scene.presets.save_preset(&preset, &scene.settings)?,
+        PresetAction::Rename(preset, new_name) => scene.presets.rename(&preset, &new_name)?,
+        PresetAction::Delete(preset) => scene.presets.delete(&preset)?,
+    }
+    scene.presets.save(path)
+}
+
+fn save_screenshot(state: Arc<RwLock<State>>, time: Time) {
+    // copied out, so the app thread isn't blocked while the screenshot renders
+    let (scene, path, options, width, height, adapter) = {
+        let state = state.read().unwrap();
+        let (scene, name) = match (state.scene(), state.scene_name()) {
+            (Some(scene), Some(name)) => (scene.clone(), name),
+            _ => {
+                eprintln!("No scene to screenshot");
+                return;
+            }
+        };
+        (
+            scene,
+            state.config.screenshot.path_for(name),
+            state.config.render_options(),
+            state.config.screenshot.width,
+            state.config.screenshot.height,
+            state.adapter_options(),
+        )
+    };
+
+    let gfx = match pollster::block_on(HeadlessGfx::new(width, height, &adapter)) {
+        Ok(gfx) => gfx,
+        Err(e) => {
+            eprintln!("Failed to create screenshot renderer: {:?}", e);
+            return;
+        }
+    };
+
+    match export::screenshot(&gfx, &scene, &options, time, &path) {
+        Ok(()) => println!("Saved screenshot {:?}", path),
+        Err(e) => eprintln!("Failed to save screenshot: {}", e),
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * Drives the active playlist, changing scene every interval or by time of day
+ * without an active playlist, next and previous step through every loaded scene
+ */
+use std::{
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    app::{AppEvent, AppState},
+    io::{Playlist, PlaylistEntry, PlaylistMode},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum PlaylistMessage {
+    Next,
+    Previous,
+    Quit,
+}
+
+// xorshift, shuffles don't need to be any better than this
+fn shuffle<T>(items: &mut [T]) {
+    let mut seed = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(1)
+        | 1;
+    for index in (1..items.len()).rev() {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        items.swap(index, (seed % (index as u64 + 1)) as usize);
+    }
+}
+
+struct Driver {
+    app_state: AppState,
+    playlist: Option<Playlist>,
+    order: Vec<PlaylistEntry>,
+    position: usize,
+    last_change: Instant,
+    // the entry last chosen by the time of day schedule
+    scheduled: Option<PlaylistEntry>,
+}
+
+impl Driver {
+    fn new(app_state: AppState) -> Driver {
+        Driver {
+            app_state,
+            playlist: None,
+            order: Vec::new(),
+            position: 0,
+            last_change: Instant::now(),
+            scheduled: None,
+        }
+    }
+
+    // scenes are played in this order, starting from the current scene if it's in the playlist
+    fn reset(&mut self, playlist: Option<Playlist>) {
+        let current = self
+            .app_state
+            .get()
+            .scene_name()
+            .map(|name| name.to_string());
+
+        self.order = match playlist.as_ref() {
+            Some(playlist) => playlist.scenes.clone(),
+            None => Vec::new(),
+        };
+        if let Some(Playlist {
+            mode: PlaylistMode::Shuffle,
+            ..
+        }) = playlist
+        {
+            shuffle(&mut self.order);
+        }
+
+        self.position = current
+            .and_then(|current| self.order.iter().position(|entry| entry.scene == current))
+            .unwrap_or(0);
+        self.last_change = Instant::now();
+        self.scheduled = None;
+        self.playlist = playlist;
+    }
+
+    fn set_scene(&mut self, scene: String, preset: Option<String>) {
+        self.last_change = Instant::now();
+        self.app_state.send(AppEvent::SetScene(scene)).ok();
+        // handled after the scene has changed
+        if let Some(preset) = preset {
+            self.app_state.send(AppEvent::ApplyPreset(preset)).ok();
+        }
+    }
+
+    fn step(&mut self, forward: bool) {
+        // every loaded scene when there is no playlist
+        if self.order.is_empty() {
+            let state = self.app_state.get();
+            if state.scenes.is_empty() {
+                return;
+            }
+            let count = state.scenes.len();
+            let index = match (state.current_scene(), forward) {
+                (Some(index), true) => (index + 1) % count,
+                (Some(index), false) => (index + count - 1) % count,
+                (None, _) => 0,
+            };
+            let scene = state.scenes[index].name.to_string();
+            drop(state);
+            self.set_scene(scene, None);
+            return;
+        }
+
+        let count = self.order.len();
+        self.position = if forward {
+            (self.position + 1) % count
+        } else {
+            (self.position + count - 1) % count
+        };
+
+        // a shuffled playlist is shuffled again after each pass
+        if forward && self.position == 0 {
+            if let Some(Playlist {
+                mode: PlaylistMode::Shuffle,
+                ..
+            }) = self.playlist
+            {
+                shuffle(&mut self.order);
+            }
+        }
+
+        let entry = self.order[self.position].clone();
+        self.set_scene(entry.scene, entry.preset);
+    }
+
+    fn update(&mut self) {
+        let playlist = self
+            .app_state
+            .get()
+            .config
+            .playlists
+            .active_playlist()
+            .cloned();
+        if playlist != self.playlist {
+            self.reset(playlist);
+        }
+
+        let (mode, interval_minutes, scheduled) = match self.playlist.as_ref() {
+            Some(playlist) if !playlist.scenes.is_empty() => (
+                playlist.mode,
+                playlist.interval_minutes,
+                playlist.entry_at(chrono::Local::now().time()).cloned(),
+            ),
+            _ => return,
+        };
+
+        match mode {
+            PlaylistMode::InOrder | PlaylistMode::Shuffle => {
+                let interval = Duration::from_secs(interval_minutes.max(1) as u64 * 60);
+                if self.last_change.elapsed() >= interval {
+                    self.step(true);
+                }
+            }
+            // only on schedule changes, so next and previous still work in between
+            PlaylistMode::TimeOfDay => {
+                let entry = match scheduled {
+                    Some(entry) if Some(&entry) != self.scheduled.as_ref() => entry,
+                    _ => return,
+                };
+                self.scheduled = Some(entry.clone());
+                if let Some(position) = self.order.iter().position(|scheduled| *scheduled == entry)
+                {
+                    self.position = position;
+                }
+                if Some(entry.scene.as_str()) != self.app_state.get().scene_name() {
+                    self.set_scene(entry.scene, entry.preset);
+                } else if let Some(preset) = entry.preset {
+                    self.app_state.send(AppEvent::ApplyPreset(preset)).ok();
+                }
+            }
+        }
+    }
+}
+
+pub fn run(app_state: AppState, receiver: Receiver<PlaylistMessage>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut driver = Driver::new(app_state);
+
+        loop {
+            driver.update();
+
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(PlaylistMessage::Next) => driver.step(true),
+                Ok(PlaylistMessage::Previous) => driver.step(false),
+                Ok(PlaylistMessage::Quit) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+        }
+    })
+}
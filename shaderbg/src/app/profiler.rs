@@ -0,0 +1,74 @@
+/*
+ * Collects frame statistics for the performance window, only while it's open
+ */
+use std::time::{Duration, Instant};
+
+use crate::{app::AppState, io::FrameTarget};
+use shaderbg_render::{gfx::Gfx, scene::Resources};
+
+pub struct Profiler {
+    target: FrameTarget,
+    profiling: bool,
+    last_frame: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new(target: FrameTarget) -> Profiler {
+        Profiler {
+            target,
+            profiling: false,
+            last_frame: None,
+        }
+    }
+
+    // called before rendering, so gpu timing is enabled for the frame
+    pub fn begin(&mut self, app_state: &AppState, gfx: &Gfx, resources: Option<&mut Resources>) {
+        let profiling = app_state.get_stats().profiling;
+        if profiling != self.profiling {
+            self.profiling = profiling;
+            self.last_frame = None;
+            if profiling {
+                *app_state.stats_mut().target_mut(self.target) = Default::default();
+            }
+        }
+
+        if let Some(resources) = resources {
+            resources.set_profiling(&gfx.device, &gfx.queue, profiling);
+        }
+    }
+
+    pub fn end(
+        &mut self,
+        app_state: &AppState,
+        gfx: &Gfx,
+        resources: Option<&mut Resources>,
+        frame_time: Duration,
+    ) {
+        if !self.profiling {
+            return;
+        }
+
+        let now = Instant::now();
+        let interval = self
+            .last_frame
+            .map_or(frame_time, |last_frame| now - last_frame);
+        self.last_frame = Some(now);
+
+        let (passes, memory) = match resources {
+            Some(resources) => (
+                resources.read_pass_times(&gfx.device),
+                resources.memory_usage(),
+            ),
+            None => (None, Vec::new()),
+        };
+
+        let mut stats = app_state.stats_mut();
+        let stats = stats.target_mut(self.target);
+        stats.record_frame(
+            frame_time.as_secs_f32() * 1000.0,
+            interval.as_secs_f32() * 1000.0,
+        );
+        stats.passes = passes;
+        stats.memory = memory;
+    }
+}
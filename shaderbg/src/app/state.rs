@@ -6,17 +6,20 @@
  */
 use std::sync::{
     mpsc::{SendError, Sender},
-    Arc, RwLock, RwLockReadGuard,
+    Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
 };
 
 use crate::{
-    app::{AppEvent, AppEventSender, AppMessage},
+    app::{AppEvent, AppEventSender, AppMessage, Clock},
     io::{
         scenes::{load_scenes, SceneEntry},
-        Args, Config, StartupWith, TrayConfig,
+        AdapterArgs, Args, Config, FrameTarget, StartupWith, TrayConfig,
     },
 };
-use shaderbg_render::{gfx::buffer::Time, scene::Scene};
+use shaderbg_render::{
+    gfx::{buffer::Time, profiler::FrameStats, AdapterOptions},
+    scene::Scene,
+};
 
 pub struct State {
     pub config: Config,
@@ -24,7 +27,9 @@ pub struct State {
     pub tray_open: bool,
     pub background_open: bool,
     pub scenes: Box<[SceneEntry]>,
+    pub clock: Clock,
     current_scene: Option<usize>,
+    adapter_args: AdapterArgs,
 }
 
 impl State {
@@ -89,10 +94,17 @@ impl State {
             background_open,
             config,
             scenes,
+            clock: Clock::new(),
             current_scene,
+            adapter_args: args.adapter,
         }
     }
 
+    // the command line overrides the config, for windows opened from now on
+    pub fn adapter_options(&self) -> AdapterOptions {
+        self.adapter_args.apply(&self.config.adapter).options()
+    }
+
     pub fn scene(&self) -> Option<&Scene> {
         match self.current_scene {
             Some(index) => {
@@ -156,6 +168,86 @@ impl State {
             false
         }
     }
+
+    // loads every scene again, the current scene is found by name in the new list
+    // returns true when the current scene is still loaded
+    // setting values are kept for scenes that are still there
+    pub fn rescan_scenes(&mut self) -> bool {
+        if !self.config.scene_dir.is_dir() {
+            eprintln!("Scene directory {:?} not found", self.config.scene_dir);
+            return self.current_scene.is_some();
+        }
+
+        let name = self.scene_name().map(|name| name.to_string());
+        let mut scenes = load_scenes(
+            self.config.scene_dir.clone(),
+            self.config.settings_dir.clone(),
+        );
+        for entry in scenes.iter_mut() {
+            if let Some(old) = self.scenes.iter().find(|old| old.name == entry.name) {
+                entry.scene.settings.merge(&old.scene.settings);
+            }
+        }
+        self.scenes = scenes;
+        self.current_scene = name.and_then(|name| {
+            self.scenes
+                .iter()
+                .position(|entry| entry.name.as_ref() == name)
+        });
+
+        self.current_scene.is_some()
+    }
+
+    // returns true when the replaced scene is the current one
+    // scenes added to the directory since startup are appended
+    // setting values are kept, they may not have been saved yet
+    pub fn replace_scene(&mut self, name: &str, mut scene: Scene) -> bool {
+        let index = self
+            .scenes
+            .iter()
+            .position(|entry| entry.name.as_ref() == name);
+
+        match index {
+            Some(index) => {
+                scene.settings.merge(&self.scenes[index].scene.settings);
+                self.scenes[index].scene = scene;
+                self.current_scene == Some(index)
+            }
+            None => {
+                let mut scenes = std::mem::take(&mut self.scenes).into_vec();
+                scenes.push(SceneEntry {
+                    name: name.into(),
+                    scene,
+                });
+                self.scenes = scenes.into_boxed_slice();
+                false
+            }
+        }
+    }
+}
+
+// written by the renderers themselves while profiling, the performance window reads it
+#[derive(Default)]
+pub struct PerformanceStats {
+    pub profiling: bool,
+    pub window: FrameStats,
+    pub background: FrameStats,
+}
+
+impl PerformanceStats {
+    pub fn target(&self, target: FrameTarget) -> &FrameStats {
+        match target {
+            FrameTarget::Window => &self.window,
+            FrameTarget::Background => &self.background,
+        }
+    }
+
+    pub fn target_mut(&mut self, target: FrameTarget) -> &mut FrameStats {
+        match target {
+            FrameTarget::Window => &mut self.window,
+            FrameTarget::Background => &mut self.background,
+        }
+    }
 }
 
 /*
@@ -167,6 +259,7 @@ impl State {
 pub struct AppState {
     state: Arc<RwLock<State>>,
     time: Arc<RwLock<Time>>,
+    stats: Arc<RwLock<PerformanceStats>>,
     owner: AppEventSender,
     pub app_tx: Sender<AppMessage>,
 }
@@ -181,6 +274,7 @@ impl AppState {
         AppState {
             state,
             time,
+            stats: Arc::new(RwLock::new(PerformanceStats::default())),
             app_tx,
             owner,
         }
@@ -194,6 +288,14 @@ impl AppState {
         self.time.read().unwrap()
     }
 
+    pub fn get_stats(&self) -> RwLockReadGuard<'_, PerformanceStats> {
+        self.stats.read().unwrap()
+    }
+
+    pub fn stats_mut(&self) -> RwLockWriteGuard<'_, PerformanceStats> {
+        self.stats.write().unwrap()
+    }
+
     pub fn send(&self, event: AppEvent) -> Result<(), SendError<AppMessage>> {
         self.app_tx.send((event, self.owner.clone()))
     }
@@ -202,6 +304,7 @@ impl AppState {
         AppState {
             state: self.state.clone(),
             time: self.time.clone(),
+            stats: self.stats.clone(),
             owner,
             app_tx: self.app_tx.clone(),
         }
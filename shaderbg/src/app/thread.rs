@@ -1,14 +1,18 @@
 /*
  * Window thread.  Runs the Event Loop and handles all WindowEvent messages
  */
+use std::time::{Duration, Instant};
 use tao::{
     event::{Event, StartCause},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
 };
 
 use crate::{
-    app::{AppEvent, AppEventSender, AppState, Background, MenuBuilder, Tray, Window, Windows},
-    io::UiTheme,
+    app::{
+        frame_interval, AppEvent, AppEventSender, AppState, Background, MenuBuilder, Tray, Window,
+        Windows,
+    },
+    io::{FrameTarget, UiTheme},
 };
 use shaderbg_render::scene::io::setting::SettingValue;
 
@@ -24,6 +28,9 @@ pub enum ThreadEvent {
     UpdateTheme(UiTheme),
     RebuildMenus,
     SceneChanged,
+    // true when the reloaded scene is the current one
+    SceneReloaded(bool),
+    FrameRateChanged,
     Quit,
 }
 
@@ -36,6 +43,8 @@ enum ThreadEventTarget {
 pub struct EventLoopThread {
     window: Option<Window>,
     tray: Option<Tray>,
+    // when the loop next wakes to draw the window and background
+    next_tick: Instant,
 }
 
 impl EventLoopThread {
@@ -46,6 +55,7 @@ impl EventLoopThread {
             EventLoopThread {
                 window: None,
                 tray: None,
+                next_tick: Instant::now(),
             },
             event_loop,
         )
@@ -70,6 +80,43 @@ impl EventLoopThread {
         }
     }
 
+    // wakes for the most frequent capped target, instead of spinning between frames
+    fn control_flow(&mut self, app_state: &AppState, background_open: bool) -> ControlFlow {
+        let state = app_state.get();
+        let mut targets = Vec::new();
+        if self.window.is_some() {
+            targets.push(FrameTarget::Window);
+        }
+        if background_open {
+            targets.push(FrameTarget::Background);
+        }
+
+        let mut interval: Option<Duration> = None;
+        for target in targets {
+            let max_fps = state.config.frame_rate.max_fps(target, state.scene());
+            match frame_interval(max_fps) {
+                Some(target_interval) => {
+                    interval = Some(
+                        interval.map_or(target_interval, |interval| interval.min(target_interval)),
+                    );
+                }
+                None => return ControlFlow::Poll,
+            }
+        }
+
+        match interval {
+            Some(interval) => {
+                let now = Instant::now();
+                if now >= self.next_tick {
+                    self.next_tick = (self.next_tick + interval).max(now);
+                }
+                ControlFlow::WaitUntil(self.next_tick)
+            }
+            // nothing is drawing, so only wake for events
+            None => ControlFlow::Wait,
+        }
+    }
+
     pub fn run(
         mut self,
         event_loop: EventLoop<ThreadEvent>,
@@ -181,6 +228,16 @@ impl EventLoopThread {
                             window.scene_changed();
                         }
                     }
+                    ThreadEvent::SceneReloaded(current) => {
+                        if let Some(window) = self.window.as_mut() {
+                            window.scene_reloaded(current);
+                        }
+                    }
+                    ThreadEvent::FrameRateChanged => {
+                        if let Some(window) = self.window.as_mut() {
+                            window.frame_rate_changed();
+                        }
+                    }
                     ThreadEvent::UpdateTheme(theme) => {
                         if let Some(window) = self.window.as_mut() {
                             window.update_theme(theme);
@@ -234,6 +291,10 @@ impl EventLoopThread {
                             .send(AppEvent::BackgroundEvent(Event::MainEventsCleared))
                             .unwrap();
                     }
+                    if *control_flow != ControlFlow::Exit {
+                        *control_flow =
+                            self.control_flow(&app_state, background_window_id.is_some());
+                    }
                 }
                 Event::MenuEvent { .. } => {
                     menu_builder.handle_event(event);
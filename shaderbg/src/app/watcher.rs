@@ -0,0 +1,213 @@
+/*
+ * Watches the scene directory, reloading scenes when their files change
+ * modification times are polled, and new versions are built on a headless device
+ * before they replace the loaded scene, so one that fails to compile is never used
+ */
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    app::{AppEvent, AppState},
+    io::scenes::load_scene,
+};
+use shaderbg_render::{
+    gfx::{AdapterOptions, HeadlessGfx},
+    scene::{RenderOptions, Resources, Scene},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// scenes are built this small to check they compile
+const VALIDATE_SIZE: u32 = 64;
+
+pub enum WatcherMessage {
+    // the scenes were loaded again, so the files on disk are up to date
+    Rescan,
+    Quit,
+}
+
+// changes when any file in a scene is written, added or removed
+#[derive(PartialEq)]
+struct Signature {
+    modified: Option<SystemTime>,
+    files: usize,
+    bytes: u64,
+}
+
+impl Signature {
+    fn add(&mut self, path: &Path) {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return,
+        };
+
+        if metadata.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    self.add(&entry.path());
+                }
+            }
+            return;
+        }
+
+        self.files += 1;
+        self.bytes += metadata.len();
+        if let Ok(modified) = metadata.modified() {
+            self.modified = self.modified.max(Some(modified));
+        }
+    }
+}
+
+// scenes are folders or zip archives, named like load_scenes names them
+fn scan(scene_dir: &Path) -> HashMap<String, Signature> {
+    let mut scenes = HashMap::new();
+    let entries = match std::fs::read_dir(scene_dir) {
+        Ok(entries) => entries,
+        Err(_) => return scenes,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = match entry.file_name().to_str() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if !(path.is_dir() || name.ends_with(".zip")) {
+            continue;
+        }
+
+        let mut signature = Signature {
+            modified: None,
+            files: 0,
+            bytes: 0,
+        };
+        signature.add(&path);
+        scenes.insert(name, signature);
+    }
+
+    scenes
+}
+
+// the device is made on the first reload, and again when the configured adapter changes
+fn validation_gfx<'a>(
+    gfx: &'a mut Option<(AdapterOptions, HeadlessGfx)>,
+    adapter: &AdapterOptions,
+) -> Result<&'a HeadlessGfx, String> {
+    if !matches!(gfx, Some((options, _)) if options == adapter) {
+        *gfx = None;
+        let headless = pollster::block_on(HeadlessGfx::new(VALIDATE_SIZE, VALIDATE_SIZE, adapter))
+            .map_err(|e| format!("{:?}", e))?;
+        *gfx = Some((adapter.clone(), headless));
+    }
+
+    match gfx {
+        Some((_, headless)) => Ok(headless),
+        None => Err("no validation device".to_string()),
+    }
+}
+
+fn reload(
+    name: &str,
+    scene_dir: PathBuf,
+    settings_dir: PathBuf,
+    options: &RenderOptions,
+    gfx: &HeadlessGfx,
+) -> Result<Scene, String> {
+    let scene = load_scene(name, scene_dir, settings_dir).map_err(|e| format!("{:?}", e))?;
+
+    pollster::block_on(Resources::new_checked(
+        &scene,
+        &gfx.device,
+        VALIDATE_SIZE,
+        VALIDATE_SIZE,
+        gfx.format(),
+        options,
+    ))
+    .map_err(|e| format!("{:?}", e))?;
+
+    Ok(scene)
+}
+
+pub fn run(app_state: AppState, receiver: Receiver<WatcherMessage>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut scene_dir = app_state.get().config.scene_dir.clone();
+        let mut known = scan(&scene_dir);
+        // editors often save in several writes, so changes wait until a poll sees no difference
+        let mut pending: HashMap<String, Signature> = HashMap::new();
+        // built on the adapter the scene is rendered with
+        let mut gfx = None;
+
+        loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(WatcherMessage::Quit) | Err(RecvTimeoutError::Disconnected) => break,
+                Ok(WatcherMessage::Rescan) => {
+                    known = scan(&scene_dir);
+                    pending.clear();
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            let (current_dir, settings_dir, options, adapter) = {
+                let state = app_state.get();
+                (
+                    state.config.scene_dir.clone(),
+                    state.config.settings_dir.clone(),
+                    state.config.render_options(),
+                    state.adapter_options(),
+                )
+            };
+
+            // a new scene directory is scanned, not reloaded
+            if current_dir != scene_dir {
+                scene_dir = current_dir;
+                known = scan(&scene_dir);
+                pending.clear();
+                continue;
+            }
+
+            for (name, signature) in scan(&scene_dir) {
+                if known.get(&name) == Some(&signature) {
+                    pending.remove(&name);
+                    continue;
+                }
+                if pending.get(&name) != Some(&signature) {
+                    pending.insert(name, signature);
+                    continue;
+                }
+
+                pending.remove(&name);
+                known.insert(name.clone(), signature);
+
+                let reloaded = validation_gfx(&mut gfx, &adapter).and_then(|gfx| {
+                    reload(
+                        &name,
+                        scene_dir.clone(),
+                        settings_dir.clone(),
+                        &options,
+                        gfx,
+                    )
+                });
+                match reloaded {
+                    Ok(scene) => {
+                        println!("Reloaded scene {}", name);
+                        app_state
+                            .send(AppEvent::SceneReloaded(name, Box::new(scene)))
+                            .ok();
+                    }
+                    Err(e) => {
+                        eprintln!(
+                            "Failed to reload scene {}, keeping the loaded version",
+                            name
+                        );
+                        eprintln!("error: {}", e);
+                    }
+                }
+            }
+        }
+    })
+}
@@ -10,8 +10,8 @@ use tao::{
 };
 
 use crate::{
-    app::{AppEvent, AppState, MenuBuilder, ThreadEvent},
-    io::{TrayConfig, UiTheme},
+    app::{AppEvent, AppState, FrameLimiter, MenuBuilder, Profiler, ThreadEvent},
+    io::{FrameTarget, TrayConfig, UiTheme},
     ui::AppUi,
 };
 use shaderbg_render::{
@@ -25,6 +25,7 @@ pub enum Windows {
     SceneBrowser,
     ConfigureBackground,
     Settings,
+    Playlists,
     Performance,
 }
 
@@ -35,6 +36,8 @@ pub struct Window {
     settings: Option<Settings>,
     resources: Option<Resources>,
     shadertoy: ShaderToy,
+    frame_limiter: FrameLimiter,
+    profiler: Profiler,
     app_ui: AppUi,
 }
 
@@ -62,32 +65,27 @@ impl Window {
             window.set_focus();
         }
 
-        let gfx_context = GfxContext::new(&window);
+        let gfx_context = GfxContext::new(&window, app_state.get().adapter_options());
 
         let size = window.inner_size();
-        let gfx = pollster::block_on(Gfx::new(gfx_context, size.width, size.height, true));
+        let mut gfx = pollster::block_on(Gfx::new(gfx_context, size.width, size.height, true));
 
         let app_ui = AppUi::new(gfx.ui.as_ref().unwrap(), &window, app_state.clone());
 
         let shadertoy = ShaderToy::new();
 
-        let (resources, settings) = if let Some(scene) = app_state.get().scene() {
-            (
-                Some(
-                    Resources::new(
-                        scene,
-                        &gfx.device,
-                        gfx.config.width,
-                        gfx.config.height,
-                        gfx.config.format,
-                    )
-                    .unwrap(),
-                ),
-                Some(scene.settings.clone()),
-            )
-        } else {
-            (None, None)
-        };
+        let resources = Window::build_resources(&gfx, &app_state);
+
+        let state = app_state.get();
+        gfx.set_present_mode(state.config.frame_rate.present_mode.present_mode());
+        let frame_limiter = FrameLimiter::new(
+            state
+                .config
+                .frame_rate
+                .max_fps(FrameTarget::Window, state.scene()),
+        );
+        let settings = state.scene().map(|scene| scene.settings.clone());
+        drop(state);
 
         Window {
             window,
@@ -100,6 +98,8 @@ impl Window {
             // browser: None,
             // settings_ui: None,
             shadertoy,
+            frame_limiter,
+            profiler: Profiler::new(FrameTarget::Window),
             app_ui,
         }
     }
@@ -110,7 +110,7 @@ impl Window {
         }
 
         if let Some(resources) = self.resources.as_mut() {
-            resources.update_setting(key, value);
+            resources.update_setting(&self.gfx.device, key, value);
         }
     }
 
@@ -145,7 +145,7 @@ impl Window {
             } => {
                 self.gfx.resized(width, height);
                 if let Some(resources) = self.resources.as_mut() {
-                    resources.resize(width, height);
+                    resources.resize(&self.gfx.device, width, height);
                 }
             }
             Event::WindowEvent {
@@ -158,7 +158,7 @@ impl Window {
             } => {
                 self.gfx.resized(*width, *height);
                 if let Some(resources) = self.resources.as_mut() {
-                    resources.resize(*width, *height);
+                    resources.resize(&self.gfx.device, *width, *height);
                 }
             }
             Event::WindowEvent {
@@ -196,7 +196,11 @@ impl Window {
                 }
             }
             Event::MainEventsCleared => self.window.request_redraw(),
-            Event::RedrawEventsCleared => {
+            Event::RedrawEventsCleared if self.frame_limiter.ready() => {
+                if self.gfx.device_lost() {
+                    self.recover_device();
+                }
+
                 let mut changes = Vec::new();
                 let time = { *self.app_state.get_time() };
 
@@ -209,23 +213,38 @@ impl Window {
 
                 let mut reload_browser = false;
                 let mut scene_ui_result = SceneUiResult::Open;
+                let mut preset_actions = Vec::new();
 
+                let frozen = self.app_state.get().clock.frozen();
+                if let Some(resources) = self.resources.as_mut() {
+                    resources.set_frozen(&self.gfx.device, frozen);
+                }
+
+                self.profiler
+                    .begin(&self.app_state, &self.gfx, self.resources.as_mut());
+                let started = std::time::Instant::now();
                 let full_output = self.gfx.render(
                     self.resources.as_mut(),
                     time,
                     self.shadertoy,
                     Some(self.app_ui.get_input(&self.window)),
-                    |ctx, gfx| {
+                    |ctx, _| {
                         self.app_ui.render(
                             ctx,
-                            gfx,
                             self.settings.as_ref(),
                             &mut changes,
                             &mut scene_ui_result,
+                            &mut preset_actions,
                             &mut reload_browser,
                         );
                     },
                 );
+                self.profiler.end(
+                    &self.app_state,
+                    &self.gfx,
+                    self.resources.as_mut(),
+                    started.elapsed(),
+                );
 
                 for (key, value) in changes {
                     self.app_state
@@ -237,6 +256,14 @@ impl Window {
                     self.app_state.send(AppEvent::SceneSettingsSaved).unwrap();
                 }
 
+                for action in preset_actions {
+                    self.app_state.send(AppEvent::Preset(action)).unwrap();
+                }
+
+                if reload_browser {
+                    self.app_state.send(AppEvent::RescanScenes).unwrap();
+                }
+
                 if let Some(full_output) = full_output {
                     self.app_ui.handle_full_output(
                         full_output.platform_output,
@@ -252,7 +279,7 @@ impl Window {
     }
 
     pub fn open_ui_window(&mut self, window: Windows) {
-        self.app_ui.open_window(window, &self.gfx);
+        self.app_ui.open_window(window);
     }
 
     pub fn rebuild_menus(&mut self, menu_builder: &mut MenuBuilder) {
@@ -260,27 +287,87 @@ impl Window {
         self.window.set_menu(Some(menu));
     }
 
+    fn build_resources(gfx: &Gfx, app_state: &AppState) -> Option<Resources> {
+        let state = app_state.get();
+        let scene = state.scene()?;
+
+        // validation errors come back as None rather than reaching the device error handler
+        match pollster::block_on(Resources::new_checked(
+            scene,
+            &gfx.device,
+            gfx.config.width,
+            gfx.config.height,
+            gfx.config.format,
+            &state.config.render_options(),
+        )) {
+            Ok(resources) => Some(resources),
+            Err(error) => {
+                eprintln!("failed to load scene: {error:?}");
+                None
+            }
+        }
+    }
+
     pub fn scene_changed(&mut self) {
-        if let Some(scene) = self.app_state.get().scene() {
-            self.resources = Some(
-                Resources::new(
-                    scene,
-                    &self.gfx.device,
-                    self.gfx.config.width,
-                    self.gfx.config.height,
-                    self.gfx.config.format,
-                )
-                .unwrap(),
-            );
+        self.frame_rate_changed();
+
+        // the old scene stays on screen until the new one is built, then blends into it
+        let old = self.resources.take();
+        self.resources = Window::build_resources(&self.gfx, &self.app_state);
+        let transition = self.app_state.get().config.transition.clone();
+        if let (Some(old), Some(kind), Some(_)) = (old, transition.kind(), &self.resources) {
+            self.gfx.start_transition(old, kind, transition.duration());
+        }
 
+        let state = self.app_state.get();
+        if let Some(scene) = state.scene() {
             self.settings = Some(scene.settings.clone());
             self.app_ui.update_scene_ui(scene);
         } else {
-            self.resources = None;
             self.settings = None;
         }
     }
 
+    // the new scene was already built by the watcher, but if it still fails the old one keeps running
+    pub fn scene_reloaded(&mut self, current: bool) {
+        self.app_ui.reload_browser();
+        if !current {
+            return;
+        }
+
+        self.frame_rate_changed();
+
+        if let Some(resources) = Window::build_resources(&self.gfx, &self.app_state) {
+            self.resources = Some(resources);
+        }
+
+        let state = self.app_state.get();
+        if let Some(scene) = state.scene() {
+            self.settings = Some(scene.settings.clone());
+            self.app_ui.update_scene_ui(scene);
+        }
+    }
+
+    // everything on the gpu went with the old device, so build it again
+    fn recover_device(&mut self) {
+        if let Err(error) = pollster::block_on(self.gfx.recover()) {
+            eprintln!("failed to recover graphics device: {error:?}");
+            return;
+        }
+
+        self.app_ui.reload_browser();
+        self.resources = Window::build_resources(&self.gfx, &self.app_state);
+    }
+
+    pub fn frame_rate_changed(&mut self) {
+        let state = self.app_state.get();
+        let frame_rate = &state.config.frame_rate;
+        self.gfx
+            .set_present_mode(frame_rate.present_mode.present_mode());
+        self.frame_limiter
+            .set_max_fps(frame_rate.max_fps(FrameTarget::Window, state.scene()));
+    }
+
     pub fn update_theme(&mut self, theme: UiTheme) {
         let visuals = match theme {
             UiTheme::Dark => egui::Visuals::dark(),
@@ -0,0 +1,178 @@
+/*
+ * Renders scenes offscreen, to screenshots, png sequences or a video stream
+ * exports use a fixed clock so every run produces the same frames
+ */
+use std::{io::Write, path::Path};
+
+use shaderbg_render::{
+    gfx::{
+        buffer::{ShaderToy, Time},
+        AdapterOptions, HeadlessGfx,
+    },
+    scene::{RenderOptions, Resources, Scene},
+};
+
+use crate::io::{scenes::load_scene, Args, Config, RenderArgs, StreamFormat};
+
+pub fn render(args: &RenderArgs, config: &Config, adapter: AdapterOptions) -> Result<(), String> {
+    if args.out.is_none() && args.stream.is_none() {
+        return Err("Nothing to write, pass --out and/or --stream".to_string());
+    }
+    if args.width == 0 || args.height == 0 || args.fps == 0 {
+        return Err("Width, height and fps must be greater than zero".to_string());
+    }
+
+    let scene = load_scene(
+        &args.scene,
+        config.scene_dir.clone(),
+        config.settings_dir.clone(),
+    )
+    .map_err(|error| format!("Failed to load scene {}: {:?}", args.scene, error))?;
+
+    let adapter = AdapterOptions {
+        force_fallback: adapter.force_fallback || args.software,
+        ..adapter
+    };
+    let gfx = pollster::block_on(HeadlessGfx::new(args.width, args.height, &adapter))
+        .map_err(|error| format!("Failed to create renderer: {:?}", error))?;
+    eprintln!("Rendering with {}", gfx.adapter_info.name);
+
+    let mut resources = Resources::new(
+        &scene,
+        &gfx.device,
+        args.width,
+        args.height,
+        gfx.format(),
+        &RenderOptions::default(),
+    )
+    .map_err(|error| format!("Failed to create scene resources: {:?}", error))?;
+
+    if let Some(out) = args.out.as_ref() {
+        std::fs::create_dir_all(out).map_err(|error| error.to_string())?;
+    }
+
+    let frames = (args.duration.as_secs_f64() * args.fps as f64).round() as u64;
+    let digits = frames.to_string().len().max(5);
+
+    let stdout = std::io::stdout();
+    let mut stream = std::io::BufWriter::new(stdout.lock());
+    if let Some(StreamFormat::Y4m) = args.stream {
+        writeln!(
+            stream,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+            args.width, args.height, args.fps
+        )
+        .map_err(|error| error.to_string())?;
+    }
+
+    let dt = 1.0 / args.fps as f64;
+    let mut time = Time::new();
+    let mut shadertoy = ShaderToy::new();
+    let (width, height) = resources.size();
+
+    for frame in 0..frames {
+        let now = (frame * 1000 / args.fps as u64) as u32;
+        time.update_time(now, dt);
+        shadertoy.update(now, dt, width, height);
+
+        gfx.render(&mut resources, time, shadertoy);
+        let pixels = read_frame(&gfx)?;
+
+        if let Some(out) = args.out.as_ref() {
+            let path = out.join(format!("frame_{:0digits$}.png", frame, digits = digits));
+            save_png(&path, &pixels, args.width, args.height)?;
+        }
+
+        match args.stream {
+            Some(StreamFormat::Y4m) => write_y4m_frame(&mut stream, &pixels),
+            Some(StreamFormat::Raw) => stream.write_all(&pixels),
+            None => Ok(()),
+        }
+        .map_err(|error| error.to_string())?;
+
+        eprint!("\rFrame {}/{}", frame + 1, frames);
+    }
+    eprintln!();
+
+    stream.flush().map_err(|error| error.to_string())
+}
+
+// the --screenshot flag, taking the scene from the arguments or the config
+pub fn screenshot_to(args: &Args, path: &Path, config: &Config) -> Result<(), String> {
+    let name = args
+        .scene
+        .as_ref()
+        .or(config.scene.as_ref())
+        .ok_or_else(|| "No scene to screenshot, pass --scene".to_string())?;
+    let (width, height) = args
+        .screenshot_size
+        .unwrap_or((config.screenshot.width, config.screenshot.height));
+
+    let scene = load_scene(name, config.scene_dir.clone(), config.settings_dir.clone())
+        .map_err(|error| format!("Failed to load scene {}: {:?}", name, error))?;
+
+    let adapter = args.adapter.apply(&config.adapter).options();
+    let gfx = pollster::block_on(HeadlessGfx::new(width, height, &adapter))
+        .map_err(|error| format!("Failed to create renderer: {:?}", error))?;
+
+    screenshot(&gfx, &scene, &config.render_options(), Time::new(), path)
+}
+
+// renders one frame of the scene at the renderer's size and saves it as a png
+pub fn screenshot(
+    gfx: &HeadlessGfx,
+    scene: &Scene,
+    options: &RenderOptions,
+    time: Time,
+    path: &Path,
+) -> Result<(), String> {
+    let (width, height) = gfx.size();
+    let mut resources = Resources::new(scene, &gfx.device, width, height, gfx.format(), options)
+        .map_err(|error| format!("Failed to create scene resources: {:?}", error))?;
+
+    let mut shadertoy = ShaderToy::new();
+    let (scene_width, scene_height) = resources.size();
+    shadertoy.update(time.time, time.dt as f64, scene_width, scene_height);
+
+    gfx.render(&mut resources, time, shadertoy);
+    let pixels = read_frame(gfx)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    save_png(path, &pixels, width, height)
+}
+
+fn read_frame(gfx: &HeadlessGfx) -> Result<Vec<u8>, String> {
+    let mut pixels = gfx
+        .read_rgba()
+        .map_err(|error| format!("Failed to read frame: {:?}", error))?;
+
+    // the desktop ignores alpha, so exported frames do too
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel[3] = 255;
+    }
+
+    Ok(pixels)
+}
+
+fn save_png(path: &Path, pixels: &[u8], width: u32, height: u32) -> Result<(), String> {
+    image::save_buffer(path, pixels, width, height, image::ColorType::Rgba8)
+        .map_err(|error| format!("Failed to write {:?}: {}", path, error))
+}
+
+// converts rgba to limited range bt.601 planes
+fn write_y4m_frame(stream: &mut impl Write, pixels: &[u8]) -> std::io::Result<()> {
+    let count = pixels.len() / 4;
+    let mut planes = vec![0u8; count * 3];
+
+    for (index, pixel) in pixels.chunks_exact(4).enumerate() {
+        let (r, g, b) = (pixel[0] as i32, pixel[1] as i32, pixel[2] as i32);
+        planes[index] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        planes[count + index] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+        planes[count * 2 + index] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+    }
+
+    stream.write_all(b"FRAME\n")?;
+    stream.write_all(&planes)
+}
@@ -1,7 +1,10 @@
 /*
  * Command line arguments
  */
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{path::PathBuf, time::Duration};
+
+use crate::io::{AdapterConfig, BackendConfig, PowerPreferenceConfig};
 
 /// lightweight animated backgrounds.
 #[derive(Parser, Debug)]
@@ -25,4 +28,128 @@ pub struct Args {
 
     #[arg(long, hide = true)]
     pub system_startup: bool,
+
+    /// Save a screenshot of the scene to a PNG and exit
+    #[arg(long, value_name = "PATH")]
+    pub screenshot: Option<PathBuf>,
+
+    /// Screenshot resolution, e.g. 2560x1440 (defaults to the configured size)
+    #[arg(long, value_name = "WxH", value_parser = parse_size)]
+    pub screenshot_size: Option<(u32, u32)>,
+
+    /// Print the graphics adapters available, and exit
+    #[arg(long)]
+    pub list_adapters: bool,
+
+    #[command(flatten)]
+    pub adapter: AdapterArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// overrides for the configured adapter, these aren't saved
+#[derive(clap::Args, Clone, Debug)]
+pub struct AdapterArgs {
+    /// Prefer the integrated or discrete GPU
+    #[arg(long)]
+    pub power_preference: Option<PowerPreferenceConfig>,
+
+    /// Graphics API to draw with
+    #[arg(long)]
+    pub backend: Option<BackendConfig>,
+
+    /// Use the first adapter with a name containing this (see --list-adapters)
+    #[arg(long, value_name = "NAME")]
+    pub adapter: Option<String>,
+
+    /// Use the fallback (software) adapter
+    #[arg(long)]
+    pub fallback_adapter: bool,
+}
+
+impl AdapterArgs {
+    pub fn apply(&self, config: &AdapterConfig) -> AdapterConfig {
+        AdapterConfig {
+            power_preference: self.power_preference.unwrap_or(config.power_preference),
+            backend: self.backend.unwrap_or(config.backend),
+            name: self.adapter.clone().or_else(|| config.name.clone()),
+            fallback: self.fallback_adapter || config.fallback,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Render a scene to numbered PNGs and/or a video stream, without opening a window
+    Render(RenderArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RenderArgs {
+    /// Scene to render
+    #[arg(short, long)]
+    pub scene: String,
+
+    #[arg(long, default_value_t = 1920)]
+    pub width: u32,
+
+    #[arg(long, default_value_t = 1080)]
+    pub height: u32,
+
+    #[arg(long, default_value_t = 60)]
+    pub fps: u32,
+
+    /// Length of the clip, e.g. 10s, 1500ms or 2m
+    #[arg(long, default_value = "10s", value_parser = parse_duration)]
+    pub duration: Duration,
+
+    /// Directory to write numbered PNG frames to
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Write frames to stdout, for piping into an encoder
+    #[arg(long)]
+    pub stream: Option<StreamFormat>,
+
+    /// Render with the software adapter
+    #[arg(long)]
+    pub software: bool,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum StreamFormat {
+    /// YUV4MPEG2 (4:4:4), understood by ffmpeg and most encoders
+    Y4m,
+    /// Headerless rgba frames
+    Raw,
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let size = value.split_once('x').and_then(|(width, height)| {
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!("{} is not a valid size, expected WxH", value)),
+    }
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix('m') {
+        (number, 60.0)
+    } else {
+        (value, 1.0)
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(number) if number > 0.0 => Ok(Duration::from_secs_f64(number * scale)),
+        _ => Err(format!("{} is not a valid duration", value)),
+    }
 }
@@ -2,7 +2,15 @@
  * config, as well as file read / save
  */
 
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use shaderbg_render::{
+    gfx::{
+        resolution::DynamicResolution, transition::TransitionKind, AdapterOptions, Backends,
+        PowerPreference, PresentMode,
+    },
+    scene::{io::post_process::PostEffect, RenderOptions, Scene},
+};
 use std::collections::VecDeque;
 
 const CONFIG_FILE: &str = "config.toml";
@@ -52,6 +60,416 @@ impl std::fmt::Debug for TrayConfig {
     }
 }
 
+#[derive(Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Antialiasing {
+    #[default]
+    Scene,
+    Disabled,
+}
+
+impl std::fmt::Debug for Antialiasing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scene => write!(f, "Scene default"),
+            Self::Disabled => write!(f, "Disabled"),
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostProcessMode {
+    #[default]
+    Scene,
+    Disabled,
+    Custom,
+}
+
+impl std::fmt::Debug for PostProcessMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Scene => write!(f, "Scene default"),
+            Self::Disabled => write!(f, "Disabled"),
+            Self::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+// effect strengths used by the custom mode, 0.0 turns an effect off
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PostProcessConfig {
+    pub mode: PostProcessMode,
+    pub bloom: f32,
+    pub vignette: f32,
+    pub grain: f32,
+    pub blur: f32,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> PostProcessConfig {
+        PostProcessConfig {
+            mode: PostProcessMode::Scene,
+            bloom: 0.5,
+            vignette: 0.5,
+            grain: 0.0,
+            blur: 0.0,
+        }
+    }
+}
+
+impl PostProcessConfig {
+    pub fn effects(&self) -> Option<Vec<PostEffect>> {
+        match self.mode {
+            PostProcessMode::Scene => None,
+            PostProcessMode::Disabled => Some(Vec::new()),
+            PostProcessMode::Custom => {
+                let mut effects = Vec::new();
+                if self.blur > 0.0 {
+                    effects.push(PostEffect::Blur {
+                        radius: Some(self.blur),
+                    });
+                }
+                if self.bloom > 0.0 {
+                    effects.push(PostEffect::Bloom {
+                        threshold: None,
+                        intensity: Some(self.bloom),
+                        radius: None,
+                    });
+                }
+                if self.vignette > 0.0 {
+                    effects.push(PostEffect::Vignette {
+                        strength: Some(self.vignette),
+                        radius: None,
+                        softness: None,
+                    });
+                }
+                if self.grain > 0.0 {
+                    effects.push(PostEffect::Grain {
+                        amount: Some(self.grain),
+                    });
+                }
+                Some(effects)
+            }
+        }
+    }
+}
+
+// resolution the background is rendered at, relative to the monitor
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RenderScaleConfig {
+    pub scale: f32,
+    // adjust the scale to reach the target fps
+    pub automatic: bool,
+    pub target_fps: f32,
+}
+
+impl Default for RenderScaleConfig {
+    fn default() -> RenderScaleConfig {
+        RenderScaleConfig {
+            scale: 1.0,
+            automatic: false,
+            target_fps: 30.0,
+        }
+    }
+}
+
+impl RenderScaleConfig {
+    pub fn dynamic_resolution(&self) -> Option<DynamicResolution> {
+        if self.automatic {
+            Some(DynamicResolution::new(self.target_fps))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentModeConfig {
+    #[default]
+    Fifo,
+    Mailbox,
+    Immediate,
+}
+
+impl std::fmt::Debug for PresentModeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fifo => write!(f, "Fifo (vsync)"),
+            Self::Mailbox => write!(f, "Mailbox"),
+            Self::Immediate => write!(f, "Immediate"),
+        }
+    }
+}
+
+impl PresentModeConfig {
+    pub fn present_mode(&self) -> PresentMode {
+        match self {
+            Self::Fifo => PresentMode::Fifo,
+            Self::Mailbox => PresentMode::Mailbox,
+            Self::Immediate => PresentMode::Immediate,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FrameTarget {
+    Window,
+    Background,
+}
+
+// frame rate caps in frames per second, None is uncapped
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FrameRateConfig {
+    pub max_fps: Option<u32>,
+    pub window_max_fps: Option<u32>,
+    pub background_max_fps: Option<u32>,
+    pub present_mode: PresentModeConfig,
+}
+
+impl Default for FrameRateConfig {
+    fn default() -> FrameRateConfig {
+        FrameRateConfig {
+            max_fps: None,
+            window_max_fps: None,
+            background_max_fps: None,
+            present_mode: PresentModeConfig::Fifo,
+        }
+    }
+}
+
+impl FrameRateConfig {
+    // a target's cap overrides the scene's, which overrides the global cap
+    pub fn max_fps(&self, target: FrameTarget, scene: Option<&Scene>) -> Option<u32> {
+        let target_max_fps = match target {
+            FrameTarget::Window => self.window_max_fps,
+            FrameTarget::Background => self.background_max_fps,
+        };
+
+        target_max_fps
+            .or_else(|| scene.and_then(|scene| scene.descriptor.meta.max_fps))
+            .or(self.max_fps)
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPreferenceConfig {
+    #[default]
+    Default,
+    LowPower,
+    HighPerformance,
+}
+
+impl std::fmt::Debug for PowerPreferenceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "Default"),
+            Self::LowPower => write!(f, "Low power (integrated)"),
+            Self::HighPerformance => write!(f, "High performance (discrete)"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendConfig {
+    #[default]
+    Auto,
+    Vulkan,
+    Dx12,
+    Metal,
+    Gl,
+}
+
+impl BackendConfig {
+    pub fn backends(&self) -> Backends {
+        match self {
+            Self::Auto => Backends::all(),
+            Self::Vulkan => Backends::VULKAN,
+            Self::Dx12 => Backends::DX12,
+            Self::Metal => Backends::METAL,
+            Self::Gl => Backends::GL,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AdapterConfig {
+    pub power_preference: PowerPreferenceConfig,
+    pub backend: BackendConfig,
+    pub name: Option<String>,
+    pub fallback: bool,
+}
+
+impl AdapterConfig {
+    pub fn options(&self) -> AdapterOptions {
+        AdapterOptions {
+            backends: self.backend.backends(),
+            power_preference: match self.power_preference {
+                PowerPreferenceConfig::Default => PowerPreference::default(),
+                PowerPreferenceConfig::LowPower => PowerPreference::LowPower,
+                PowerPreferenceConfig::HighPerformance => PowerPreference::HighPerformance,
+            },
+            name: self.name.clone().filter(|name| !name.is_empty()),
+            force_fallback: self.fallback,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionStyle {
+    None,
+    #[default]
+    Crossfade,
+    Wipe,
+    Dissolve,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TransitionConfig {
+    pub style: TransitionStyle,
+    pub duration: f32,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> TransitionConfig {
+        TransitionConfig {
+            style: TransitionStyle::Crossfade,
+            duration: 1.0,
+        }
+    }
+}
+
+impl TransitionConfig {
+    // None when scenes should switch immediately
+    pub fn kind(&self) -> Option<TransitionKind> {
+        match self.style {
+            TransitionStyle::None => None,
+            TransitionStyle::Crossfade => Some(TransitionKind::Crossfade),
+            TransitionStyle::Wipe => Some(TransitionKind::Wipe),
+            TransitionStyle::Dissolve => Some(TransitionKind::Dissolve),
+        }
+    }
+
+    pub fn duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(self.duration.max(0.0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScreenshotConfig {
+    pub width: u32,
+    pub height: u32,
+    pub dir: std::path::PathBuf,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> ScreenshotConfig {
+        ScreenshotConfig {
+            width: 1920,
+            height: 1080,
+            dir: std::path::PathBuf::from("./screenshots/"),
+        }
+    }
+}
+
+impl ScreenshotConfig {
+    // screenshots are named after the scene and when they were taken
+    pub fn path_for(&self, scene: &str) -> std::path::PathBuf {
+        self.dir.join(format!(
+            "{}-{}.png",
+            scene,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistMode {
+    #[default]
+    InOrder,
+    Shuffle,
+    TimeOfDay,
+}
+
+impl std::fmt::Debug for PlaylistMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InOrder => write!(f, "In order"),
+            Self::Shuffle => write!(f, "Shuffled"),
+            Self::TimeOfDay => write!(f, "Time of day"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PlaylistEntry {
+    pub scene: String,
+    // only used by time of day playlists
+    #[serde(default)]
+    pub start: chrono::NaiveTime,
+    // applied after switching to the scene
+    #[serde(default)]
+    pub preset: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Playlist {
+    pub name: String,
+    pub mode: PlaylistMode,
+    pub interval_minutes: u32,
+    pub scenes: Vec<PlaylistEntry>,
+}
+
+impl Default for Playlist {
+    fn default() -> Playlist {
+        Playlist {
+            name: "Playlist".to_string(),
+            mode: PlaylistMode::InOrder,
+            interval_minutes: 10,
+            scenes: Vec::new(),
+        }
+    }
+}
+
+impl Playlist {
+    // the entry which started most recently, wrapping around to yesterday's last entry
+    pub fn entry_at(&self, time: chrono::NaiveTime) -> Option<&PlaylistEntry> {
+        self.scenes
+            .iter()
+            .filter(|entry| entry.start <= time)
+            .max_by_key(|entry| entry.start)
+            .or_else(|| self.scenes.iter().max_by_key(|entry| entry.start))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PlaylistConfig {
+    pub active: Option<String>,
+    pub playlists: Vec<Playlist>,
+}
+
+impl PlaylistConfig {
+    pub fn active_playlist(&self) -> Option<&Playlist> {
+        let active = self.active.as_ref()?;
+        self.playlists
+            .iter()
+            .find(|playlist| &playlist.name == active)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RecentScene {
     pub scene: String,
@@ -67,6 +485,14 @@ pub enum ConfigUpdate {
     Theme(UiTheme),
     TrayConfig(TrayConfig),
     LaunchOnStartup(bool),
+    Antialiasing(Antialiasing),
+    PostProcess(PostProcessConfig),
+    RenderScale(RenderScaleConfig),
+    Screenshot(ScreenshotConfig),
+    FrameRate(FrameRateConfig),
+    Adapter(AdapterConfig),
+    Playlists(PlaylistConfig),
+    Transition(TransitionConfig),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -74,12 +500,30 @@ pub struct Config {
     pub scene: Option<String>,
     pub scene_dir: std::path::PathBuf,
     pub settings_dir: std::path::PathBuf,
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: std::path::PathBuf,
     pub startup_with: StartupWith,
     pub startup_background: bool,
     pub theme: UiTheme,
     pub tray_config: TrayConfig,
     pub launch_on_startup: bool,
     pub background_enabled: bool,
+    #[serde(default)]
+    pub antialiasing: Antialiasing,
+    #[serde(default)]
+    pub post_process: PostProcessConfig,
+    #[serde(default)]
+    pub render_scale: RenderScaleConfig,
+    #[serde(default)]
+    pub screenshot: ScreenshotConfig,
+    #[serde(default)]
+    pub frame_rate: FrameRateConfig,
+    #[serde(default)]
+    pub adapter: AdapterConfig,
+    #[serde(default)]
+    pub transition: TransitionConfig,
+    #[serde(default)]
+    pub playlists: PlaylistConfig,
     pub recent_scenes: VecDeque<RecentScene>,
 }
 
@@ -89,18 +533,35 @@ impl Default for Config {
             scene: None,
             scene_dir: std::path::PathBuf::from("./scenes/"),
             settings_dir: std::path::PathBuf::from("./settings/"),
+            cache_dir: default_cache_dir(),
             theme: UiTheme::Dark,
             startup_with: StartupWith::Tray,
             startup_background: true,
             tray_config: TrayConfig::CloseTo,
             launch_on_startup: false,
             background_enabled: false,
+            antialiasing: Antialiasing::Scene,
+            post_process: PostProcessConfig::default(),
+            render_scale: RenderScaleConfig::default(),
+            screenshot: ScreenshotConfig::default(),
+            frame_rate: FrameRateConfig::default(),
+            adapter: AdapterConfig::default(),
+            transition: TransitionConfig::default(),
+            playlists: PlaylistConfig::default(),
             recent_scenes: VecDeque::new(),
         }
     }
 }
 
+fn default_cache_dir() -> std::path::PathBuf {
+    std::path::PathBuf::from("./cache/")
+}
+
 impl Config {
+    pub fn thumbnail_dir(&self) -> std::path::PathBuf {
+        self.cache_dir.join("thumbnails")
+    }
+
     pub fn load() -> Result<Config, String> {
         let config_string = match std::fs::read_to_string(CONFIG_FILE) {
             Ok(str) => str,
@@ -150,6 +611,55 @@ impl Config {
             ConfigUpdate::LaunchOnStartup(launch_on_startup) => {
                 self.launch_on_startup = launch_on_startup;
             }
+            ConfigUpdate::Antialiasing(antialiasing) => {
+                self.antialiasing = antialiasing;
+            }
+            ConfigUpdate::PostProcess(post_process) => {
+                self.post_process = post_process;
+            }
+            ConfigUpdate::RenderScale(render_scale) => {
+                self.render_scale = render_scale;
+            }
+            ConfigUpdate::Screenshot(screenshot) => {
+                self.screenshot = screenshot;
+            }
+            ConfigUpdate::FrameRate(frame_rate) => {
+                self.frame_rate = frame_rate;
+            }
+            ConfigUpdate::Adapter(adapter) => {
+                self.adapter = adapter;
+            }
+            ConfigUpdate::Transition(transition) => {
+                self.transition = transition;
+            }
+            ConfigUpdate::Playlists(playlists) => {
+                self.playlists = playlists;
+            }
+        }
+    }
+
+    pub fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            max_samples: match self.antialiasing {
+                Antialiasing::Scene => None,
+                Antialiasing::Disabled => Some(1),
+            },
+            post_process: self.post_process.effects(),
+            render_scale: None,
+        }
+    }
+
+    // the background can render below the monitor's resolution
+    // at full scale the scaling stage is skipped, it is added if the scale changes later
+    pub fn background_render_options(&self) -> RenderOptions {
+        let render_scale = &self.render_scale;
+        RenderOptions {
+            render_scale: if render_scale.scale == 1.0 && !render_scale.automatic {
+                None
+            } else {
+                Some(render_scale.scale)
+            },
+            ..self.render_options()
         }
     }
 
@@ -1,5 +1,5 @@
 mod args;
 mod config;
 pub mod scenes;
-pub use args::Args;
+pub use args::{AdapterArgs, Args, Command, RenderArgs, StreamFormat};
 pub use config::*;
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, io::Read, path::PathBuf};
 
-use shaderbg_render::scene::{Scene, SceneError, Settings};
+use shaderbg_render::scene::{Presets, Scene, SceneError, Settings};
 
 pub struct SceneEntry {
     pub name: Box<str>,
@@ -82,19 +82,32 @@ fn load_scene_from_zip(path: PathBuf, settings_dir: PathBuf) -> Result<Scene, Sc
 
     match Scene::load_from_memory(scene_toml_contents, files) {
         Ok(mut scene) => {
-            let dir = settings_dir.join(format!(
-                "{}.toml",
-                path.file_name().unwrap().to_str().unwrap()
-            ));
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let dir = settings_dir.join(format!("{}.toml", name));
             if let Some(settings) = Settings::load(dir, &scene.descriptor) {
                 scene.settings = settings;
             }
+            scene.presets = Presets::load(Presets::path(&settings_dir, name), &scene.descriptor);
             Ok(scene)
         }
         Err(error) => Err(error),
     }
 }
 
+// loads a single scene by name, from a folder or a .zip archive
+pub fn load_scene(
+    name: &str,
+    scene_dir: PathBuf,
+    settings_dir: PathBuf,
+) -> Result<Scene, SceneError> {
+    let archive = scene_dir.join(format!("{}.zip", name.trim_end_matches(".zip")));
+    if archive.is_file() {
+        load_scene_from_zip(archive, settings_dir)
+    } else {
+        Scene::load(name.to_string(), scene_dir, settings_dir)
+    }
+}
+
 pub fn load_scenes(scene_dir: PathBuf, settings_dir: PathBuf) -> Box<[SceneEntry]> {
     println!("Loading scenes...");
     let scene_dir_iter = match std::fs::read_dir(scene_dir.clone()) {
@@ -8,6 +8,7 @@ extern crate objc;
 
 mod app;
 pub mod egui_tao;
+mod export;
 mod io;
 mod ui;
 
@@ -25,6 +26,38 @@ fn main() {
         }
     };
 
+    if args.list_adapters {
+        let options = args.adapter.apply(&config.adapter).options();
+        for (index, info) in shaderbg_render::gfx::list_adapters(options.backends)
+            .iter()
+            .enumerate()
+        {
+            println!("{}: {}", index, info.name);
+            println!("   {:?}, {:?}", info.backend, info.device_type);
+            if !info.driver.is_empty() {
+                println!("   {} {}", info.driver, info.driver_info);
+            }
+        }
+        return;
+    }
+
+    if let Some(io::Command::Render(render_args)) = args.command.as_ref() {
+        let adapter = args.adapter.apply(&config.adapter).options();
+        if let Err(e) = export::render(render_args, &config, adapter) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = args.screenshot.as_ref() {
+        if let Err(e) = export::screenshot_to(&args, path, &config) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // check if shaderbg is already running
     // it is? -> tell 'window' to open and quit this process
     // https://gist.github.com/andelf/8668088 could be used for IPC
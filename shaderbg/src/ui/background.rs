@@ -1,15 +1,30 @@
-use crate::app::{AppEvent, AppState, ThreadEvent};
+use crate::{
+    app::{AppEvent, AppState, ThreadEvent},
+    io::{ConfigUpdate, PostProcessConfig, PostProcessMode, RenderScaleConfig},
+};
+use shaderbg_render::scene::MIN_RENDER_SCALE;
 
 pub struct Background {
     app_state: AppState,
+    post_process: PostProcessConfig,
+    render_scale: RenderScaleConfig,
 }
 
 impl Background {
     pub fn new(app_state: AppState) -> Background {
-        Background { app_state }
+        let (post_process, render_scale) = {
+            let config = &app_state.get().config;
+            (config.post_process.clone(), config.render_scale.clone())
+        };
+
+        Background {
+            app_state,
+            post_process,
+            render_scale,
+        }
     }
 
-    pub fn render(&self, ui: &mut egui::Ui) {
+    pub fn render(&mut self, ui: &mut egui::Ui) {
         let mut background_enabled = self.app_state.get().background_open;
 
         if ui.checkbox(&mut background_enabled, "Enabled").changed() {
@@ -21,5 +36,80 @@ impl Background {
                 })
                 .unwrap()
         }
+
+        ui.add_space(5.0);
+        egui::Grid::new("render_scale_grid").show(ui, |ui| {
+            ui.label("Render scale");
+            ui.add(
+                egui::Slider::new(&mut self.render_scale.scale, MIN_RENDER_SCALE..=1.0)
+                    .custom_formatter(|scale, _| format!("{:.0}%", scale * 100.0)),
+            );
+            ui.end_row();
+            ui.label("Adjust automatically");
+            ui.checkbox(&mut self.render_scale.automatic, "");
+            ui.end_row();
+            if self.render_scale.automatic {
+                ui.label("Target FPS");
+                ui.add(egui::Slider::new(
+                    &mut self.render_scale.target_fps,
+                    15.0..=144.0,
+                ));
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(5.0);
+        ui.label("Post-processing");
+        ui.horizontal(|ui| {
+            for mode in [
+                PostProcessMode::Scene,
+                PostProcessMode::Disabled,
+                PostProcessMode::Custom,
+            ] {
+                let label = format!("{:?}", mode);
+                ui.selectable_value(&mut self.post_process.mode, mode, label);
+            }
+        });
+
+        if self.post_process.mode == PostProcessMode::Custom {
+            egui::Grid::new("post_process_grid").show(ui, |ui| {
+                ui.label("Bloom");
+                ui.add(egui::Slider::new(&mut self.post_process.bloom, 0.0..=2.0));
+                ui.end_row();
+                ui.label("Vignette");
+                ui.add(egui::Slider::new(
+                    &mut self.post_process.vignette,
+                    0.0..=1.0,
+                ));
+                ui.end_row();
+                ui.label("Film grain");
+                ui.add(egui::Slider::new(&mut self.post_process.grain, 0.0..=0.3));
+                ui.end_row();
+                ui.label("Blur");
+                ui.add(egui::Slider::new(&mut self.post_process.blur, 0.0..=16.0));
+                ui.end_row();
+            });
+        }
+
+        // the scene is rebuilt when applied, so don't update while dragging sliders
+        let mut changes = Vec::new();
+        {
+            let config = &self.app_state.get().config;
+            if self.post_process != config.post_process {
+                changes.push(ConfigUpdate::PostProcess(self.post_process.clone()));
+            }
+            if self.render_scale != config.render_scale {
+                changes.push(ConfigUpdate::RenderScale(self.render_scale.clone()));
+            }
+        }
+
+        if ui
+            .add_enabled(!changes.is_empty(), egui::Button::new("Apply"))
+            .clicked()
+        {
+            self.app_state
+                .send(AppEvent::ConfigUpdated(changes.into_boxed_slice()))
+                .unwrap();
+        }
     }
 }
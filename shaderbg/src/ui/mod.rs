@@ -1,8 +1,10 @@
 mod background;
 mod performance;
+mod playlists;
 mod settings;
 pub use background::*;
 pub use performance::*;
+pub use playlists::*;
 pub use settings::*;
 
 use tao::{
@@ -11,12 +13,17 @@ use tao::{
 };
 
 use crate::{
-    app::{AppEvent, AppState, ThreadEvent, Windows},
+    app::{AppEvent, AppState, ClockEvent, ThreadEvent, Windows, MAX_SPEED, MIN_SPEED},
     egui_tao,
     io::UiTheme,
 };
 use shaderbg_render::{
-    gfx::{self, buffer::Time, ui::SceneUiResult, Gfx},
+    gfx::{
+        self,
+        buffer::Time,
+        ui::{PresetAction, SceneUiResult},
+        Gfx,
+    },
     scene::{io::setting::SettingValue, Scene, Settings as SceneSettings},
 };
 
@@ -27,6 +34,7 @@ pub struct AppUi {
     browser: Option<gfx::ui::Browser>,
     background: Option<Background>,
     settings: Option<Settings>,
+    playlists: Option<Playlists>,
     performance: Option<Performance>,
 }
 
@@ -58,6 +66,7 @@ impl AppUi {
             browser: None,
             background: None,
             settings: None,
+            playlists: None,
             performance: None,
         }
     }
@@ -91,15 +100,25 @@ impl AppUi {
 
     pub fn update_browser(&mut self, gfx: &mut Gfx, time: Time) {
         if let Some(browser) = self.browser.as_mut() {
+            let state = self.app_state.get();
             browser.update_previews(
                 gfx.ui.as_mut().unwrap().renderer_mut(),
                 &gfx.queue,
                 &mut gfx.device,
                 time,
+                |name| state.get_scene(name.to_string()),
             );
         }
     }
 
+    // previews are rendered when the browser is created, so create it again
+    // after the device is lost or a scene is reloaded from disk
+    pub fn reload_browser(&mut self) {
+        if self.browser.is_some() {
+            self.browser = Self::browser(&self.app_state);
+        }
+    }
+
     pub fn update_scene_ui(&mut self, scene: &Scene) {
         if self.scene.is_some() {
             self.scene = Some(gfx::ui::Scene::new(&scene.descriptor, &scene.settings));
@@ -110,15 +129,15 @@ impl AppUi {
         Some(gfx::ui::Scene::new(&scene.descriptor, &scene.settings))
     }
 
-    fn browser(app_state: &AppState, gfx: &Gfx) -> Option<gfx::ui::Browser> {
+    fn browser(app_state: &AppState) -> Option<gfx::ui::Browser> {
+        let state = app_state.get();
         Some(gfx::ui::Browser::new(
-            app_state
-                .get()
+            state
                 .scenes
                 .iter()
                 .map(|entry| (entry.name.clone().to_string(), &entry.scene))
                 .collect(),
-            &gfx.device,
+            Some(state.config.thumbnail_dir().as_path()),
         ))
     }
 
@@ -130,17 +149,59 @@ impl AppUi {
         Some(Settings::new(app_state.clone()))
     }
 
-    fn performance() -> Option<Performance> {
-        Some(Performance::new())
+    fn playlists(app_state: &AppState) -> Option<Playlists> {
+        Some(Playlists::new(app_state.clone()))
     }
 
-    fn main_menu(&mut self, ui: &mut egui::Ui, gfx: &Gfx) {
+    fn performance(app_state: &AppState) -> Option<Performance> {
+        Some(Performance::new(app_state.clone()))
+    }
+
+    fn main_menu(&mut self, ui: &mut egui::Ui) {
         let mut window = None;
 
         if self.app_state.get().scene().is_some() {
             ui.label("Scene");
-            if ui.button("Pause").clicked() {}
-            if ui.button("Reload").clicked() {}
+            let (mut speed, paused, mut frozen) = {
+                let clock = &self.app_state.get().clock;
+                (clock.speed(), clock.paused(), clock.frozen())
+            };
+            let mut seconds = self.app_state.get_time().time as f64 / 1000.0;
+            let mut clock_events = Vec::new();
+
+            if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                clock_events.push(ClockEvent::TogglePause);
+            }
+            if ui.button("Reload").clicked() {
+                self.app_state.send(AppEvent::RescanScenes).unwrap();
+            }
+            ui.horizontal(|ui| {
+                ui.label("Time");
+                let scrub = egui::DragValue::new(&mut seconds)
+                    .speed(0.05)
+                    .clamp_range(0.0..=(u32::MAX / 1000) as f64)
+                    .suffix(" s");
+                if ui.add(scrub).changed() {
+                    clock_events.push(ClockEvent::Seek((seconds * 1000.0) as u32));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Speed");
+                let slider = egui::Slider::new(&mut speed, MIN_SPEED..=MAX_SPEED)
+                    .logarithmic(true)
+                    .suffix("x");
+                if ui.add(slider).changed() {
+                    clock_events.push(ClockEvent::Speed(speed));
+                }
+            });
+            if ui.checkbox(&mut frozen, "Freeze on frame").changed() {
+                clock_events.push(ClockEvent::Freeze(frozen));
+            }
+
+            for event in clock_events {
+                self.app_state.send(AppEvent::Clock(event)).unwrap();
+            }
+
             if ui.button("Scene Settings").clicked() {
                 window = Some(Windows::SceneSettings);
             }
@@ -157,22 +218,25 @@ impl AppUi {
         if ui.button("Settings").clicked() {
             window = Some(Windows::Settings);
         }
+        if ui.button("Playlists").clicked() {
+            window = Some(Windows::Playlists);
+        }
         if ui.button("Performance").clicked() {
             window = Some(Windows::Performance);
         }
 
         if let Some(window) = window {
-            self.open_window(window, gfx);
+            self.open_window(window);
         }
     }
 
     pub fn render(
         &mut self,
         ctx: &egui::Context,
-        gfx: &Gfx,
         settings: Option<&SceneSettings>,
         changes: &mut Vec<(String, SettingValue)>,
         scene_ui_result: &mut SceneUiResult,
+        preset_actions: &mut Vec<PresetAction>,
         browser_reload: &mut bool,
     ) {
         let title = if let Some(scene) = self.app_state.get().scene() {
@@ -186,17 +250,24 @@ impl AppUi {
             .resizable(false)
             .id("shaderbg".into())
             .show(ctx, |ui: &mut egui::Ui| {
-                self.main_menu(ui, gfx);
+                self.main_menu(ui);
             });
 
         if let Some(scene) = self.scene.as_mut() {
+            let presets = self
+                .app_state
+                .get()
+                .scene()
+                .map(|scene| scene.presets.clone())
+                .unwrap_or_default();
             let mut open = true;
             egui::Window::new("Scene Settings")
                 .open(&mut open)
                 .resizable(false)
                 .show(ctx, |ui| {
                     if let Some(settings) = settings {
-                        *scene_ui_result = scene.render(ui, settings, changes);
+                        *scene_ui_result =
+                            scene.render(ui, settings, &presets, changes, Some(preset_actions));
                     } else {
                         ui.heading("An error occurred");
                     }
@@ -214,7 +285,7 @@ impl AppUi {
         }
 
         let mut browser_open = true;
-        if let Some(browser) = self.browser.as_ref() {
+        if let Some(browser) = self.browser.as_mut() {
             egui::Window::new("Scene Browser")
                 .open(&mut browser_open)
                 .resizable(false)
@@ -226,12 +297,10 @@ impl AppUi {
                         Some(browser_reload),
                     );
 
-                    if let Some(scene) = scene {
-                        self.app_state
-                            .send(AppEvent::SetScene(
-                                self.app_state.get().scenes[scene].name.to_string(),
-                            ))
-                            .unwrap();
+                    // the browser can be a frame behind a rescan, so scenes are chosen by name
+                    let name = scene.and_then(|scene| browser.scene_name(scene));
+                    if let Some(name) = name.map(str::to_string) {
+                        self.app_state.send(AppEvent::SetScene(name)).unwrap();
                     }
                 });
         }
@@ -240,7 +309,7 @@ impl AppUi {
         }
 
         let mut background_open = true;
-        if let Some(background) = self.background.as_ref() {
+        if let Some(background) = self.background.as_mut() {
             egui::Window::new("Configure Background")
                 .open(&mut background_open)
                 .resizable(false)
@@ -265,9 +334,22 @@ impl AppUi {
             self.settings.take();
         }
 
+        let mut playlists_open = true;
+        if let Some(playlists) = self.playlists.as_mut() {
+            egui::Window::new("Playlists")
+                .open(&mut playlists_open)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    playlists.render(ui);
+                });
+        }
+        if !playlists_open {
+            self.playlists.take();
+        }
+
         let mut performance_open = true;
         if let Some(performance) = self.performance.as_ref() {
-            egui::Window::new("Configure Background")
+            egui::Window::new("Performance")
                 .open(&mut performance_open)
                 .resizable(false)
                 .show(ctx, |ui| {
@@ -279,11 +361,11 @@ impl AppUi {
         }
     }
 
-    pub fn open_window(&mut self, window: Windows, gfx: &Gfx) {
+    pub fn open_window(&mut self, window: Windows) {
         match window {
             Windows::SceneBrowser => {
                 if self.browser.is_none() {
-                    self.browser = Self::browser(&self.app_state, gfx);
+                    self.browser = Self::browser(&self.app_state);
                 }
             }
             Windows::SceneSettings => {
@@ -298,9 +380,14 @@ impl AppUi {
                     self.settings = Self::settings(&self.app_state);
                 }
             }
+            Windows::Playlists => {
+                if self.playlists.is_none() {
+                    self.playlists = Self::playlists(&self.app_state);
+                }
+            }
             Windows::Performance => {
                 if self.performance.is_none() {
-                    self.performance = Self::performance();
+                    self.performance = Self::performance(&self.app_state);
                 }
             }
             Windows::ConfigureBackground => {
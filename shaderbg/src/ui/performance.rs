@@ -1,11 +1,145 @@
-pub struct Performance {}
+use egui::plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::{
+    app::{AppState, PerformanceStats},
+    io::FrameTarget,
+};
+
+const PLOT_WIDTH: f32 = 320.0;
+const PLOT_HEIGHT: f32 = 90.0;
+
+// renderers only collect stats while this is open
+pub struct Performance {
+    app_state: AppState,
+}
 
 impl Performance {
-    pub fn new() -> Performance {
-        Performance {}
+    pub fn new(app_state: AppState) -> Performance {
+        app_state.stats_mut().profiling = true;
+
+        Performance { app_state }
     }
 
     pub fn render(&self, ui: &mut egui::Ui) {
-        ui.label("Not implemented");
+        let background_open = self.app_state.get().background_open;
+        let stats = self.app_state.get_stats();
+
+        Self::render_target(ui, FrameTarget::Window, &stats);
+        if background_open {
+            ui.separator();
+            Self::render_target(ui, FrameTarget::Background, &stats);
+        }
+    }
+
+    fn render_target(ui: &mut egui::Ui, target: FrameTarget, stats: &PerformanceStats) {
+        let (name, id) = match target {
+            FrameTarget::Window => ("Window", "window"),
+            FrameTarget::Background => ("Background", "background"),
+        };
+        let stats = stats.target(target);
+
+        ui.heading(name);
+        ui.label(format!(
+            "{:.0} fps, {:.2} ms per frame",
+            stats.fps(),
+            stats.average_frame_ms()
+        ));
+
+        Plot::new(format!("{}_frame_time_plot", id))
+            .width(PLOT_WIDTH)
+            .height(PLOT_HEIGHT)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .include_y(0.0)
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(history(stats.frame_ms.iter().copied())).name("Frame (ms)"));
+                plot_ui.line(
+                    Line::new(history(stats.interval_ms.iter().copied())).name("Interval (ms)"),
+                );
+            });
+
+        Plot::new(format!("{}_fps_plot", id))
+            .width(PLOT_WIDTH)
+            .height(PLOT_HEIGHT)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .include_y(0.0)
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                let fps = stats
+                    .interval_ms
+                    .iter()
+                    .map(|ms| if *ms > 0.0 { 1000.0 / ms } else { 0.0 });
+                plot_ui.line(Line::new(history(fps)).name("FPS"));
+            });
+
+        egui::CollapsingHeader::new("GPU passes")
+            .id_source(format!("{}_passes", id))
+            .default_open(true)
+            .show(ui, |ui| match stats.passes.as_ref() {
+                Some(passes) => {
+                    egui::Grid::new(format!("{}_passes_grid", id)).show(ui, |ui| {
+                        for pass in passes {
+                            ui.label(&pass.label);
+                            ui.label(format!("{:.3} ms", pass.ms));
+                            ui.end_row();
+                        }
+                        ui.strong("Total");
+                        ui.strong(format!(
+                            "{:.3} ms",
+                            passes.iter().map(|pass| pass.ms).sum::<f32>()
+                        ));
+                        ui.end_row();
+                    });
+                }
+                None => {
+                    ui.label("Timestamp queries aren't supported by this adapter");
+                }
+            });
+
+        egui::CollapsingHeader::new("GPU memory")
+            .id_source(format!("{}_memory", id))
+            .show(ui, |ui| {
+                egui::Grid::new(format!("{}_memory_grid", id)).show(ui, |ui| {
+                    for memory in stats.memory.iter() {
+                        ui.label(&memory.label);
+                        ui.label(format_bytes(memory.bytes));
+                        ui.end_row();
+                    }
+                    ui.strong("Total");
+                    ui.strong(format_bytes(stats.memory_bytes()));
+                    ui.end_row();
+                });
+            });
+    }
+}
+
+impl Drop for Performance {
+    fn drop(&mut self) {
+        self.app_state.stats_mut().profiling = false;
+    }
+}
+
+fn history(values: impl Iterator<Item = f32>) -> PlotPoints {
+    values
+        .enumerate()
+        .map(|(frame, value)| [frame as f64, value as f64])
+        .collect()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let bytes = bytes as f64;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes / MB)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes / KB)
+    } else {
+        format!("{} B", bytes)
     }
 }
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use chrono::Timelike;
+use egui::Ui;
+
+use crate::{
+    app::{AppEvent, AppState, PlaylistMessage},
+    io::{ConfigUpdate, Playlist, PlaylistConfig, PlaylistEntry, PlaylistMode},
+};
+
+pub struct Playlists {
+    app_state: AppState,
+    playlists: PlaylistConfig,
+    scenes: Vec<String>,
+    presets: HashMap<String, Vec<String>>,
+}
+
+impl Playlists {
+    pub fn new(app_state: AppState) -> Playlists {
+        let (playlists, scenes, presets) = {
+            let state = app_state.get();
+            (
+                state.config.playlists.clone(),
+                state
+                    .scenes
+                    .iter()
+                    .map(|entry| entry.name.to_string())
+                    .collect(),
+                state
+                    .scenes
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry.name.to_string(),
+                            entry
+                                .scene
+                                .presets
+                                .iter()
+                                .map(|preset| preset.name.clone())
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            )
+        };
+
+        Playlists {
+            app_state,
+            playlists,
+            scenes,
+            presets,
+        }
+    }
+
+    fn scene_combo(ui: &mut Ui, id: String, scene: &mut String, scenes: &[String]) {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(scene.as_str())
+            .show_ui(ui, |ui| {
+                for name in scenes {
+                    ui.selectable_value(scene, name.clone(), name);
+                }
+            });
+    }
+
+    fn preset_combo(ui: &mut Ui, id: String, preset: &mut Option<String>, presets: &[String]) {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(preset.as_deref().unwrap_or("Default"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(preset, None, "Default");
+                for name in presets {
+                    ui.selectable_value(preset, Some(name.clone()), name);
+                }
+            });
+    }
+
+    fn start_time(ui: &mut Ui, start: &mut chrono::NaiveTime) {
+        let mut hour = start.hour();
+        let mut minute = start.minute();
+        let changed = ui
+            .add(egui::DragValue::new(&mut hour).clamp_range(0..=23))
+            .changed()
+            | ui.add(egui::DragValue::new(&mut minute).clamp_range(0..=59))
+                .changed();
+        if changed {
+            if let Some(time) = chrono::NaiveTime::from_hms_opt(hour, minute, 0) {
+                *start = time;
+            }
+        }
+    }
+
+    fn playlist(
+        ui: &mut Ui,
+        index: usize,
+        playlist: &mut Playlist,
+        scenes: &[String],
+        presets: &HashMap<String, Vec<String>>,
+    ) -> bool {
+        let mut remove = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut playlist.name);
+        });
+        ui.horizontal(|ui| {
+            for mode in [
+                PlaylistMode::InOrder,
+                PlaylistMode::Shuffle,
+                PlaylistMode::TimeOfDay,
+            ] {
+                let label = format!("{:?}", mode);
+                ui.selectable_value(&mut playlist.mode, mode, label);
+            }
+        });
+        if playlist.mode != PlaylistMode::TimeOfDay {
+            ui.horizontal(|ui| {
+                ui.label("Change every");
+                ui.add(
+                    egui::DragValue::new(&mut playlist.interval_minutes)
+                        .clamp_range(1..=1440)
+                        .suffix(" min"),
+                );
+            });
+        }
+
+        let mut moved = None;
+        let mut removed = None;
+        let count = playlist.scenes.len();
+        egui::Grid::new(format!("playlist_scenes_{}", index)).show(ui, |ui| {
+            for (position, entry) in playlist.scenes.iter_mut().enumerate() {
+                let scene = entry.scene.clone();
+                Self::scene_combo(
+                    ui,
+                    format!("playlist_scene_{}_{}", index, position),
+                    &mut entry.scene,
+                    scenes,
+                );
+                // presets belong to a scene
+                if entry.scene != scene {
+                    entry.preset = None;
+                }
+                Self::preset_combo(
+                    ui,
+                    format!("playlist_preset_{}_{}", index, position),
+                    &mut entry.preset,
+                    presets.get(&entry.scene).map(Vec::as_slice).unwrap_or(&[]),
+                );
+                if playlist.mode == PlaylistMode::TimeOfDay {
+                    ui.horizontal(|ui| {
+                        ui.label("from");
+                        Self::start_time(ui, &mut entry.start);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(position > 0, egui::Button::new("^"))
+                        .clicked()
+                    {
+                        moved = Some((position, position - 1));
+                    }
+                    if ui
+                        .add_enabled(position + 1 < count, egui::Button::new("v"))
+                        .clicked()
+                    {
+                        moved = Some((position, position + 1));
+                    }
+                    if ui.button("Remove").clicked() {
+                        removed = Some(position);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+        if let Some((from, to)) = moved {
+            playlist.scenes.swap(from, to);
+        }
+        if let Some(position) = removed {
+            playlist.scenes.remove(position);
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!scenes.is_empty(), egui::Button::new("Add scene"))
+                .clicked()
+            {
+                playlist.scenes.push(PlaylistEntry {
+                    scene: scenes[0].clone(),
+                    start: chrono::NaiveTime::default(),
+                    preset: None,
+                });
+            }
+            if ui.button("Remove playlist").clicked() {
+                remove = true;
+            }
+        });
+
+        remove
+    }
+
+    pub fn render(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Active playlist");
+            let selected = self.playlists.active.as_deref().unwrap_or("None");
+            egui::ComboBox::from_id_source("active_playlist_combo")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.playlists.active, None, "None");
+                    for playlist in &self.playlists.playlists {
+                        ui.selectable_value(
+                            &mut self.playlists.active,
+                            Some(playlist.name.clone()),
+                            playlist.name.as_str(),
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Previous scene").clicked() {
+                self.app_state
+                    .send(AppEvent::Playlist(PlaylistMessage::Previous))
+                    .unwrap();
+            }
+            if ui.button("Next scene").clicked() {
+                self.app_state
+                    .send(AppEvent::Playlist(PlaylistMessage::Next))
+                    .unwrap();
+            }
+        });
+
+        ui.add_space(5.0);
+        let mut removed = None;
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                for (index, playlist) in self.playlists.playlists.iter_mut().enumerate() {
+                    // the active playlist is found by name, so follow renames
+                    let active = self.playlists.active.as_ref() == Some(&playlist.name);
+                    egui::CollapsingHeader::new(playlist.name.as_str())
+                        .id_source(format!("playlist_{}", index))
+                        .show(ui, |ui| {
+                            if Self::playlist(ui, index, playlist, &self.scenes, &self.presets) {
+                                removed = Some(index);
+                            }
+                        });
+                    if active {
+                        self.playlists.active = Some(playlist.name.clone());
+                    }
+                }
+            });
+        if let Some(index) = removed {
+            let playlist = self.playlists.playlists.remove(index);
+            if self.playlists.active.as_ref() == Some(&playlist.name) {
+                self.playlists.active = None;
+            }
+        }
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            if ui.button("New playlist").clicked() {
+                let mut name = "Playlist".to_string();
+                let mut number = 1;
+                while self
+                    .playlists
+                    .playlists
+                    .iter()
+                    .any(|playlist| playlist.name == name)
+                {
+                    number += 1;
+                    name = format!("Playlist {}", number);
+                }
+                self.playlists.playlists.push(Playlist {
+                    name,
+                    ..Default::default()
+                });
+            }
+
+            let changed = self.playlists != self.app_state.get().config.playlists;
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(changed, egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    self.app_state
+                        .send(AppEvent::ConfigUpdated(
+                            vec![ConfigUpdate::Playlists(self.playlists.clone())]
+                                .into_boxed_slice(),
+                        ))
+                        .unwrap();
+                }
+            });
+        });
+    }
+}
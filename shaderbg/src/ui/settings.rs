@@ -2,7 +2,11 @@ use egui::{Ui, WidgetText};
 
 use crate::{
     app::{AppEvent, AppState, ThreadEvent},
-    io::{ConfigUpdate, StartupWith, TrayConfig, UiTheme},
+    io::{
+        AdapterConfig, Antialiasing, BackendConfig, ConfigUpdate, FrameRateConfig,
+        PowerPreferenceConfig, PresentModeConfig, ScreenshotConfig, StartupWith, TransitionConfig,
+        TransitionStyle, TrayConfig, UiTheme,
+    },
 };
 
 enum SettingsError {
@@ -19,6 +23,13 @@ pub struct Settings {
     startup_background: bool,
     ui_theme: UiTheme,
     tray_config: TrayConfig,
+    antialiasing: Antialiasing,
+    frame_rate: FrameRateConfig,
+    transition: TransitionConfig,
+    adapter: AdapterConfig,
+    adapter_name: String,
+    screenshot: ScreenshotConfig,
+    screenshot_dir: String,
     error: Option<SettingsError>,
 }
 
@@ -32,6 +43,11 @@ impl Settings {
             startup_with,
             startup_background,
             tray_config,
+            antialiasing,
+            frame_rate,
+            transition,
+            adapter,
+            screenshot,
         ) = {
             let config = &app_state.get().config;
 
@@ -43,6 +59,11 @@ impl Settings {
                 config.startup_with.clone(),
                 config.startup_background,
                 config.tray_config.clone(),
+                config.antialiasing.clone(),
+                config.frame_rate.clone(),
+                config.transition.clone(),
+                config.adapter.clone(),
+                config.screenshot.clone(),
             )
         };
 
@@ -55,6 +76,13 @@ impl Settings {
             startup_with,
             ui_theme,
             tray_config,
+            antialiasing,
+            frame_rate,
+            transition,
+            adapter_name: adapter.name.clone().unwrap_or_default(),
+            adapter,
+            screenshot_dir: screenshot.dir.to_str().unwrap().to_string(),
+            screenshot,
             error: None,
         }
     }
@@ -111,6 +139,30 @@ impl Settings {
                 changes.push(ConfigUpdate::Theme(self.ui_theme.clone()));
             }
 
+            if self.antialiasing != config.antialiasing {
+                changes.push(ConfigUpdate::Antialiasing(self.antialiasing.clone()));
+            }
+
+            if self.frame_rate != config.frame_rate {
+                changes.push(ConfigUpdate::FrameRate(self.frame_rate.clone()));
+            }
+
+            if self.transition != config.transition {
+                changes.push(ConfigUpdate::Transition(self.transition.clone()));
+            }
+
+            self.adapter.name =
+                Some(self.adapter_name.trim().to_string()).filter(|name| !name.is_empty());
+            if self.adapter != config.adapter {
+                changes.push(ConfigUpdate::Adapter(self.adapter.clone()));
+            }
+
+            // the screenshot directory is created when the first screenshot is saved
+            self.screenshot.dir = std::path::PathBuf::from(self.screenshot_dir.clone());
+            if self.screenshot != config.screenshot {
+                changes.push(ConfigUpdate::Screenshot(self.screenshot.clone()));
+            }
+
             if self.tray_config != config.tray_config {
                 match self.tray_config {
                     TrayConfig::Enabled => {
@@ -158,6 +210,23 @@ impl Settings {
         });
     }
 
+    fn max_fps(ui: &mut Ui, label: &str, max_fps: &mut Option<u32>) {
+        ui.horizontal(|ui| {
+            let mut enabled = max_fps.is_some();
+            if ui.checkbox(&mut enabled, label).changed() {
+                *max_fps = if enabled { Some(60) } else { None };
+            }
+            if let Some(max_fps) = max_fps.as_mut() {
+                ui.add(
+                    egui::DragValue::new(max_fps)
+                        .clamp_range(0..=480)
+                        .suffix(" fps"),
+                )
+                .on_hover_text("0 is unlimited");
+            }
+        });
+    }
+
     pub fn render(&mut self, ui: &mut Ui) -> bool {
         let mut open = true;
         ui.heading("Options");
@@ -204,6 +273,101 @@ impl Settings {
             &[UiTheme::Light, UiTheme::Dark, UiTheme::System],
         );
 
+        ui.add_space(10.0);
+        ui.heading("Graphics");
+        Self::combo_box(
+            ui,
+            "antialiasing_combo",
+            Some("Anti-aliasing"),
+            &mut self.antialiasing,
+            &[Antialiasing::Scene, Antialiasing::Disabled],
+        );
+        Self::combo_box(
+            ui,
+            "present_mode_combo",
+            Some("Present mode"),
+            &mut self.frame_rate.present_mode,
+            &[
+                PresentModeConfig::Fifo,
+                PresentModeConfig::Mailbox,
+                PresentModeConfig::Immediate,
+            ],
+        );
+        Self::max_fps(ui, "Limit frame rate", &mut self.frame_rate.max_fps);
+        ui.label("Scenes may set their own limit, unless overridden");
+        Self::max_fps(ui, "Window limit", &mut self.frame_rate.window_max_fps);
+        Self::max_fps(
+            ui,
+            "Background limit",
+            &mut self.frame_rate.background_max_fps,
+        );
+
+        Self::combo_box(
+            ui,
+            "transition_combo",
+            Some("Scene transition"),
+            &mut self.transition.style,
+            &[
+                TransitionStyle::None,
+                TransitionStyle::Crossfade,
+                TransitionStyle::Wipe,
+                TransitionStyle::Dissolve,
+            ],
+        );
+        if self.transition.style != TransitionStyle::None {
+            ui.horizontal(|ui| {
+                ui.label("Transition length");
+                ui.add(egui::Slider::new(&mut self.transition.duration, 0.1..=5.0).suffix(" s"));
+            });
+        }
+
+        ui.add_space(5.0);
+        ui.label("Adapter (used by windows opened after saving)");
+        Self::combo_box(
+            ui,
+            "power_preference_combo",
+            Some("GPU"),
+            &mut self.adapter.power_preference,
+            &[
+                PowerPreferenceConfig::Default,
+                PowerPreferenceConfig::LowPower,
+                PowerPreferenceConfig::HighPerformance,
+            ],
+        );
+        Self::combo_box(
+            ui,
+            "backend_combo",
+            Some("Backend"),
+            &mut self.adapter.backend,
+            &[
+                BackendConfig::Auto,
+                BackendConfig::Vulkan,
+                BackendConfig::Dx12,
+                BackendConfig::Metal,
+                BackendConfig::Gl,
+            ],
+        );
+        ui.horizontal(|ui| {
+            ui.label("Adapter name");
+            ui.text_edit_singleline(&mut self.adapter_name)
+                .on_hover_text("Uses the first adapter with a name containing this");
+        });
+        ui.checkbox(
+            &mut self.adapter.fallback,
+            "Use the fallback (software) adapter",
+        );
+
+        ui.add_space(10.0);
+        ui.heading("Screenshots");
+        ui.horizontal(|ui| {
+            ui.label("Resolution");
+            ui.add(egui::DragValue::new(&mut self.screenshot.width).clamp_range(1..=16384));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut self.screenshot.height).clamp_range(1..=16384));
+        });
+        ui.label("Screenshot Directory");
+        ui.text_edit_singleline(&mut self.screenshot_dir);
+
         ui.add_space(10.0);
         ui.heading("Paths");
         ui.label("Scene Directory");